@@ -1,19 +1,28 @@
-use crate::core::prelude::GameState;
+use crate::core::prelude::{GameState, DEFAULT_LEVEL_SIZE};
 use bevy::prelude::*; // Always useful. // Other necessaries.
+use serde::{Deserialize, Serialize}; // For getting levels onto the disk and back.
 
 // General Components.
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct DestroyWhenNotThisState(pub GameState); // Holds a given game state to destroy useless components on.
 
 // These are for anything that needs to navigate the level mesh.
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct NavAgent {
     pub junction_target: Option<usize>, // This is the junction this agent is currently headed towards. (None for no movement)
     pub junction_pool: Vec<usize>, // These are all the available junctions this node can travel towards.
     pub type_base: NavAgentType,   // This is the behavior that the agent inherits.
     pub type_modified: Option<NavAgentType>, // This is the behavior that the agent can be granted by external means.
+    pub tunnel: Option<TunnelTrip>, // How far through a tunnel this agent is. (None if it isn't in one)
 }
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub enum TunnelTrip {
+    Leaving(Vec2, Vec2), // Walking out the mouth of a tunnel. (The mouth, Where we pop out the other end)
+    Arriving,            // Popped out, walking in to the junction on the far side.
+}
+#[derive(Serialize, Deserialize, Reflect, Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum NavAgentType {
     Player,  // Pacman. Controller of their own destiny.
     Shadow,  // Blinky. Will always chase down the Player's exact position.
@@ -24,15 +33,142 @@ pub enum NavAgentType {
 }
 
 // Level Components.
+#[derive(Serialize, Deserialize, Reflect, Clone, PartialEq, Debug)]
 pub struct Junction(pub Vec2, pub JunctionType); // A position and a type, as it should be.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(Serialize, Deserialize, Reflect, Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum JunctionType {
     None,        // There's nothing special about this node. Put a pellet here.
     PowerPellet, // Hey, gotta get rid of those ghosts somehow.
     GhostHouse,  // Ghost will spawn from and return here.
     BonusItem,   // Bonus items will appear here.
 }
-#[derive(Resource)]
+impl JunctionType {
+    // Every type there is, in the order they're declared. Read off the reflection info, so new ones turn up here on their own.
+    pub fn all() -> Vec<JunctionType> {
+        let bevy::reflect::TypeInfo::Enum(enum_info) =
+            <JunctionType as bevy::reflect::Typed>::type_info()
+        else {
+            return Vec::new();
+        };
+        enum_info
+            .iter()
+            .filter_map(|variant| {
+                JunctionType::from_reflect(&bevy::reflect::DynamicEnum::new(
+                    variant.name(),
+                    bevy::reflect::DynamicVariant::Unit,
+                ))
+            })
+            .collect()
+    }
+
+    // The color this type of junction gets drawn with, in the editor and anywhere else.
+    pub fn color(&self) -> Srgba {
+        match self {
+            JunctionType::None => bevy::color::palettes::css::LAVENDER,
+            JunctionType::PowerPellet => bevy::color::palettes::css::GREEN,
+            JunctionType::GhostHouse => bevy::color::palettes::css::BLUE,
+            JunctionType::BonusItem => bevy::color::palettes::css::RED,
+        }
+    }
+}
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
 pub struct Junctions(pub Vec<Junction>); // A container of junctions, for storing with levels.
-#[derive(Resource)]
-pub struct Connections(pub Vec<Option<usize>>); // Literally just a link between two junctions. The index is the first node, the usize is the second.
+#[derive(Resource, Reflect, Clone, Default)]
+#[reflect(Resource)]
+pub struct Connections(pub Vec<Option<usize>>, pub Vec<Passage>); // Literally just a link between two junctions. The index is the first node, the usize is the second. And who can go through each slot.
+#[derive(Serialize, Deserialize, Reflect, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Passage {
+    pub allowed: AgentMask, // Who can leave a junction through this slot. Nobody makes the link one-way, coming in.
+    #[serde(default)]
+    pub kind: PassageKind, // How agents get to the other end. Both ends of a link should match.
+}
+impl Default for Passage {
+    fn default() -> Self {
+        Passage {
+            allowed: AgentMask::ALL,
+            kind: PassageKind::Corridor,
+        }
+    }
+}
+#[derive(Serialize, Deserialize, Reflect, Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum PassageKind {
+    #[default]
+    Corridor, // Straight across to the other end.
+    Tunnel, // Out the edge of the screen, and in again somewhere else.
+}
+#[derive(Serialize, Deserialize, Reflect, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct AgentMask(pub u8); // One bit per NavAgentType, in the order they're declared.
+impl AgentMask {
+    pub const ALL: AgentMask = AgentMask(0b11_1111);
+    pub const NONE: AgentMask = AgentMask(0);
+    pub const PLAYER: AgentMask = AgentMask(1 << NavAgentType::Player as u8);
+    pub const GHOSTS: AgentMask = AgentMask(
+        1 << NavAgentType::Shadow as u8
+            | 1 << NavAgentType::Sneaky as u8
+            | 1 << NavAgentType::Moody as u8
+            | 1 << NavAgentType::Pokey as u8,
+    );
+
+    pub fn allows(&self, agent_type: NavAgentType) -> bool {
+        self.0 & (1 << agent_type as u8) != 0
+    }
+
+    // The presets the editor flips through.
+    pub fn next(&self) -> Self {
+        match *self {
+            AgentMask::ALL => AgentMask::GHOSTS,
+            AgentMask::GHOSTS => AgentMask::PLAYER,
+            AgentMask::PLAYER => AgentMask::NONE,
+            _ => AgentMask::ALL,
+        }
+    }
+
+    // For the editor to tell them apart.
+    pub fn color(&self) -> Srgba {
+        match *self {
+            AgentMask::ALL => bevy::color::palettes::css::LAVENDER,
+            AgentMask::GHOSTS => bevy::color::palettes::css::HOT_PINK,
+            AgentMask::PLAYER => bevy::color::palettes::css::YELLOW,
+            AgentMask::NONE => bevy::color::palettes::css::DIM_GRAY,
+            _ => bevy::color::palettes::css::ORANGE, // Hand made.
+        }
+    }
+}
+#[derive(Resource, Serialize, Deserialize, Reflect, Clone, PartialEq, Debug)]
+#[reflect(Resource)]
+pub struct LevelInfo {
+    pub name: String, // What the level's called.
+    pub size: Vec2,   // How much room the level was laid out in.
+    #[serde(default)]
+    pub author: String, // Who made it.
+    #[serde(default)]
+    pub description: String, // A little blurb about it.
+    #[serde(default)]
+    pub par_time: Option<f32>, // How many seconds a good run should take.
+    #[serde(default)]
+    pub rules: LevelRules, // Any gameplay tweaks for this level in particular.
+}
+impl Default for LevelInfo {
+    fn default() -> Self {
+        LevelInfo {
+            name: "Untitled".to_string(),
+            size: DEFAULT_LEVEL_SIZE,
+            author: String::new(),
+            description: String::new(),
+            par_time: None,
+            rules: LevelRules::default(),
+        }
+    }
+}
+#[derive(Serialize, Deserialize, Reflect, Clone, PartialEq, Debug, Default)]
+pub struct LevelRules {
+    #[serde(default)]
+    pub agent_speed: Option<f32>, // How fast everyone moves. (None for DEFAULT_AGENT_SPEED)
+    #[serde(default)]
+    pub frightened_duration: Option<f32>, // How long a power pellet scares the ghosts for. (None for DEFAULT_FRIGHTENED_DURATION)
+    #[serde(default)]
+    pub ghost_roster: Option<Vec<NavAgentType>>, // Who comes out of each ghost house. (None for DEFAULT_GHOST_ROSTER)
+    #[serde(default)]
+    pub ghost_tunnel_speed: Option<f32>, // How much ghosts slow down in tunnels, as a fraction of their speed. (None for DEFAULT_GHOST_TUNNEL_SPEED)
+}
@@ -1,4 +1,44 @@
+use crate::components::NavAgentType;
+use bevy::math::Vec2;
+
 pub const MAX_CONNECTIONS: usize = 8;
 
 pub const EDITOR_JUNCTION_RADIUS: f32 = 25.0;
 pub const EDITOR_JUNCTION_CONNECTION_POINT_RADIUS: f32 = 9.0;
+pub const EDITOR_HISTORY_LIMIT: usize = 100;
+pub const EDITOR_GRID_SIZE: f32 = 32.0;
+pub const EDITOR_GRID_MINIMUM: f32 = 4.0;
+pub const EDITOR_GUIDE_DISTANCE: f32 = 12.0;
+pub const EDITOR_MIRROR_TOLERANCE: f32 = 12.0;
+pub const EDITOR_ZOOM_STEP: f32 = 1.1;
+pub const EDITOR_ZOOM_MINIMUM: f32 = 0.1;
+pub const EDITOR_ZOOM_MAXIMUM: f32 = 20.0;
+pub const EDITOR_SCROLL_PIXELS_PER_LINE: f32 = 100.0;
+pub const EDITOR_FRAME_MARGIN: f32 = EDITOR_JUNCTION_RADIUS * 3.0;
+pub const EDITOR_AUTO_CONNECT_TOLERANCE: f32 = 15.0; // How far off a slot's direction (in degrees) auto-connect will still link.
+pub const EDITOR_AUTO_CONNECT_RANGE: f32 = 600.0; // How far auto-connect will reach.
+pub const EDITOR_STRAIGHTEN_ITERATIONS: usize = 1000; // How long straightening keeps solving before it gives up and says so.
+pub const EDITOR_STRAIGHTEN_TOLERANCE: f32 = 0.001;
+pub const EDITOR_STRAIGHTEN_MINIMUM_LENGTH: f32 = EDITOR_JUNCTION_RADIUS * 2.0; // Straightening should never fold a link back on itself.
+pub const EDITOR_ISSUE_LIST_LENGTH: usize = 8; // How many problems the HUD bothers listing before it just counts the rest.
+
+pub const LEVEL_FILE_EXTENSION: &str = "level.ron"; // Only files ending in this get hot reloaded as levels.
+pub const DEFAULT_LEVEL_PATH: &str = "assets/levels/default.level.ron";
+pub const LEGACY_LEVEL_PATH: &str = "assets/levels/level.ron"; // Where the level got saved before it needed the .level.ron extension.
+pub const DEFAULT_PACK_PATH: &str = "assets/levels/pack.ron";
+pub const DEFAULT_ASCII_PATH: &str = "assets/levels/maze.txt";
+pub const ASCII_TILE_SIZE: f32 = 32.0;
+pub const DEFAULT_LEVEL_SIZE: Vec2 = Vec2::new(1100.0, 620.0);
+pub const DEFAULT_MAZE_SIZE: (usize, usize) = (9, 7); // (Columns, Rows)
+pub const DEFAULT_MAZE_DENSITY: u32 = 30;
+
+pub const DEFAULT_AGENT_SPEED: f32 = 250.0;
+pub const DEFAULT_FRIGHTENED_DURATION: f32 = 6.0;
+pub const DEFAULT_GHOST_ROSTER: [NavAgentType; 4] = [
+    NavAgentType::Shadow,
+    NavAgentType::Sneaky,
+    NavAgentType::Moody,
+    NavAgentType::Pokey,
+];
+pub const DEFAULT_GHOST_TUNNEL_SPEED: f32 = 0.5;
+pub const TUNNEL_MOUTH_DEPTH: f32 = 64.0; // How far agents walk out of a tunnel before popping out the other end.
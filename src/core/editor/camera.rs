@@ -0,0 +1,142 @@
+use crate::components;
+use crate::core::prelude::*;
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+// Where the editor's looking. Kept apart from the camera itself, so playing the level doesn't lose it.
+#[derive(Resource, Clone)]
+pub struct EditorView {
+    pub position: Vec2,        // The middle of the screen, in the world.
+    pub zoom: f32,             // World units per pixel.
+    pub panning: Option<Vec2>, // Where the cursor was last tick, if we're in the middle of a pan.
+}
+
+impl Default for EditorView {
+    fn default() -> Self {
+        EditorView {
+            position: Vec2::ZERO,
+            zoom: 1.0,
+            panning: None,
+        }
+    }
+}
+
+// Middle-drag off a junction (or Space and left-drag anywhere) to pan, scroll to zoom in on the cursor.
+pub(super) fn camera_input(
+    query_windows: Query<&Window, With<PrimaryWindow>>,
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut scroll_events: EventReader<MouseWheel>,
+    junctions: Res<components::Junctions>,
+    mut view: ResMut<EditorView>,
+) {
+    let window = query_windows.single();
+    let Some(cursor) = window.cursor_position() else {
+        scroll_events.clear();
+        return;
+    };
+
+    // Starting a pan. Middle clicking a junction changes its type instead, so that doesn't count.
+    let (camera, camera_transform) = query_camera.single();
+    let over_junction = camera
+        .viewport_to_world(camera_transform, cursor)
+        .map(|ray| ray.origin.truncate())
+        .is_some_and(|cursor_position| {
+            junctions
+                .0
+                .iter()
+                .any(|junction| junction.0.distance(cursor_position) < EDITOR_JUNCTION_RADIUS)
+        });
+    if (buttons.just_pressed(MouseButton::Middle) && !over_junction)
+        || (buttons.just_pressed(MouseButton::Left) && keys.pressed(KeyCode::Space))
+    {
+        view.panning = Some(cursor);
+    }
+
+    // Keeping it going. Screen space goes down where the world goes up.
+    if let Some(last_cursor) = view.panning {
+        if buttons.any_pressed([MouseButton::Middle, MouseButton::Left]) {
+            let delta = (cursor - last_cursor) * view.zoom;
+            view.position += Vec2::new(-delta.x, delta.y);
+            view.panning = Some(cursor);
+        } else {
+            view.panning = None;
+        }
+    }
+
+    // Zooming, keeping whatever's under the cursor right where it is.
+    let scrolled: f32 = scroll_events
+        .read()
+        .map(|scroll_event| match scroll_event.unit {
+            MouseScrollUnit::Line => scroll_event.y,
+            MouseScrollUnit::Pixel => scroll_event.y / EDITOR_SCROLL_PIXELS_PER_LINE,
+        })
+        .sum();
+    if scrolled != 0.0 {
+        let from_middle = Vec2::new(
+            cursor.x - window.width() * 0.5,
+            window.height() * 0.5 - cursor.y,
+        );
+        let under_cursor = view.position + from_middle * view.zoom;
+        view.zoom = (view.zoom * EDITOR_ZOOM_STEP.powf(-scrolled))
+            .clamp(EDITOR_ZOOM_MINIMUM, EDITOR_ZOOM_MAXIMUM);
+        view.position = under_cursor - from_middle * view.zoom;
+    }
+}
+
+// F to fit the whole level on screen.
+pub(super) fn frame_input(
+    query_windows: Query<&Window, With<PrimaryWindow>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    junctions: Res<components::Junctions>,
+    mut view: ResMut<EditorView>,
+) {
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        || !keys.just_pressed(KeyCode::KeyF)
+    {
+        return;
+    }
+
+    if junctions.0.is_empty() {
+        *view = EditorView::default(); // Nothing to frame, back to the start.
+        return;
+    }
+    let (minimum, maximum) = junctions.0.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(minimum, maximum), junction| (minimum.min(junction.0), maximum.max(junction.0)),
+    );
+    let size = maximum - minimum + EDITOR_FRAME_MARGIN * 2.0;
+    let window = query_windows.single();
+
+    view.position = (minimum + maximum) * 0.5;
+    view.zoom = (size.x / window.width())
+        .max(size.y / window.height())
+        .clamp(EDITOR_ZOOM_MINIMUM, EDITOR_ZOOM_MAXIMUM);
+}
+
+// Pointing the camera wherever the editor's looking.
+pub(super) fn apply_editor_view(
+    view: Res<EditorView>,
+    mut query_camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    for (mut transform, mut projection) in &mut query_camera {
+        transform.translation.x = view.position.x;
+        transform.translation.y = view.position.y;
+        projection.scale = view.zoom;
+    }
+}
+
+// The game expects the camera right where it started.
+pub(super) fn reset_camera(
+    mut query_camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    for (mut transform, mut projection) in &mut query_camera {
+        transform.translation.x = 0.0;
+        transform.translation.y = 0.0;
+        projection.scale = 1.0;
+    }
+}
@@ -0,0 +1,106 @@
+use crate::components;
+use crate::core::level::{file::LinkEnd, graph};
+use crate::core::prelude::*;
+use bevy::prelude::*;
+
+// A chunk of level lifted out on its own. Junction indices only mean anything inside it.
+#[derive(Clone, Debug)]
+pub struct Subgraph {
+    pub origin: Vec2,                         // Where the middle of it was copied from.
+    pub junctions: Vec<components::Junction>, // Positions are relative to the origin.
+    pub links: Vec<(LinkEnd, LinkEnd)>,       // Only the links with both ends inside.
+    pub passages: Vec<(LinkEnd, components::Passage)>, // Who can go through those links, if it's not everyone.
+}
+
+impl Subgraph {
+    // Lifting the picked junctions out, along with any links between them.
+    pub fn copy(
+        junctions: &components::Junctions,
+        connections: &components::Connections,
+        picked: &[usize],
+    ) -> Option<Self> {
+        if picked.is_empty() {
+            return None;
+        }
+
+        let origin = picked
+            .iter()
+            .map(|junction| junctions.0[*junction].0)
+            .sum::<Vec2>()
+            / picked.len() as f32;
+
+        // Where each junction lands in the copy.
+        let local = |junction: usize| picked.iter().position(|picked| *picked == junction);
+
+        let mut links = Vec::new();
+        let mut passages = Vec::new();
+        for (copy_index, junction) in picked.iter().enumerate() {
+            for slot in 0..MAX_CONNECTIONS {
+                let Some(other_slot) = connections.0[junction * MAX_CONNECTIONS + slot] else {
+                    continue;
+                };
+                // Both ends have to come along, and each link only the once.
+                if let Some(other_copy_index) = local(other_slot / MAX_CONNECTIONS) {
+                    let end = (copy_index, slot);
+                    let other_end = (other_copy_index, other_slot % MAX_CONNECTIONS);
+                    if end < other_end {
+                        links.push((end, other_end));
+                    }
+                    let passage = connections.1[junction * MAX_CONNECTIONS + slot];
+                    if passage != components::Passage::default() {
+                        passages.push((end, passage));
+                    }
+                }
+            }
+        }
+
+        Some(Subgraph {
+            origin,
+            junctions: picked
+                .iter()
+                .map(|junction| {
+                    components::Junction(
+                        junctions.0[*junction].0 - origin,
+                        junctions.0[*junction].1,
+                    )
+                })
+                .collect(),
+            links,
+            passages,
+        })
+    }
+
+    // Dropping a copy into the level, centred on a position. Returns the new junctions.
+    pub fn paste(
+        &self,
+        junctions: &mut components::Junctions,
+        connections: &mut components::Connections,
+        position: Vec2,
+    ) -> Vec<usize> {
+        let pasted: Vec<usize> = self
+            .junctions
+            .iter()
+            .map(|junction| {
+                graph::add_junction(junctions, connections, junction.0 + position, junction.1)
+            })
+            .collect();
+
+        // Every local index just gets shifted onto the end of the flat vector.
+        for ((first, first_slot), (second, second_slot)) in &self.links {
+            graph::link_slots(
+                connections,
+                pasted[*first] * MAX_CONNECTIONS + first_slot,
+                pasted[*second] * MAX_CONNECTIONS + second_slot,
+            );
+        }
+        for ((junction, slot), passage) in &self.passages {
+            connections.1[pasted[*junction] * MAX_CONNECTIONS + slot] = *passage;
+        }
+
+        pasted
+    }
+}
+
+// Whatever was copied last. Hangs around when the level changes, so it can go into another one.
+#[derive(Resource, Default)]
+pub struct EditorClipboard(pub Option<Subgraph>);
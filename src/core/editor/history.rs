@@ -0,0 +1,139 @@
+use crate::components;
+use crate::core::editor::MetadataField;
+use crate::core::prelude::*;
+use bevy::prelude::*;
+
+// What sort of edit was made. For the log, and for knowing which edits can be lumped together.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EditKind {
+    AddJunction,
+    MoveJunctions,
+    DeleteJunctions,
+    RetypeJunction,
+    PaintJunctions,
+    Link,
+    Unlink,
+    Restrict,
+    Tunnel,
+    AutoConnect,
+    Straighten,
+    Paste,
+    ImportMaze,
+    NewLevel,
+    Metadata(MetadataField), // Which field is being typed into.
+}
+
+impl EditKind {
+    // Drags and typing come in one little bit at a time, but they should come undone all at once.
+    fn coalesces(&self) -> bool {
+        matches!(
+            self,
+            EditKind::MoveJunctions | EditKind::PaintJunctions | EditKind::Metadata(_)
+        )
+    }
+}
+
+// Everything needed to put the level back the way it was.
+struct LevelSnapshot {
+    info: components::LevelInfo,
+    junctions: Vec<components::Junction>,
+    connections: components::Connections,
+}
+
+impl LevelSnapshot {
+    fn take(
+        info: &components::LevelInfo,
+        junctions: &components::Junctions,
+        connections: &components::Connections,
+    ) -> Self {
+        LevelSnapshot {
+            info: info.clone(),
+            junctions: junctions.0.clone(),
+            connections: connections.clone(),
+        }
+    }
+
+    fn restore(
+        self,
+        info: &mut components::LevelInfo,
+        junctions: &mut components::Junctions,
+        connections: &mut components::Connections,
+    ) {
+        *info = self.info;
+        junctions.0 = self.junctions;
+        *connections = self.connections;
+    }
+}
+
+// Every edit made in the editor, so they can be taken back.
+// Each entry holds the level as it was just before that edit.
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    undo: Vec<(EditKind, LevelSnapshot)>,
+    redo: Vec<(EditKind, LevelSnapshot)>,
+    open: Option<EditKind>, // The edit still being added to, if it's one that coalesces.
+}
+
+impl EditHistory {
+    // Call this right before changing the level.
+    pub fn record(
+        &mut self,
+        kind: EditKind,
+        info: &components::LevelInfo,
+        junctions: &components::Junctions,
+        connections: &components::Connections,
+    ) {
+        // Still the same drag (or the same bit of typing), the first snapshot has it covered.
+        if kind.coalesces() && self.open == Some(kind) {
+            return;
+        }
+
+        self.undo
+            .push((kind, LevelSnapshot::take(info, junctions, connections)));
+        if self.undo.len() > EDITOR_HISTORY_LIMIT {
+            self.undo.remove(0); // Forgetting the oldest.
+        }
+        self.redo.clear(); // New timeline.
+        self.open = Some(kind);
+    }
+
+    // The current drag or bit of typing is done. Whatever comes next gets its own step.
+    pub fn settle(&mut self) {
+        self.open = None;
+    }
+
+    // Stepping back one edit. Returns what got undone, if anything.
+    pub fn undo(
+        &mut self,
+        info: &mut components::LevelInfo,
+        junctions: &mut components::Junctions,
+        connections: &mut components::Connections,
+    ) -> Option<EditKind> {
+        let (kind, snapshot) = self.undo.pop()?;
+        self.redo
+            .push((kind, LevelSnapshot::take(info, junctions, connections)));
+        snapshot.restore(info, junctions, connections);
+        self.open = None;
+        Some(kind)
+    }
+
+    // And forward again. Returns what got redone, if anything.
+    pub fn redo(
+        &mut self,
+        info: &mut components::LevelInfo,
+        junctions: &mut components::Junctions,
+        connections: &mut components::Connections,
+    ) -> Option<EditKind> {
+        let (kind, snapshot) = self.redo.pop()?;
+        self.undo
+            .push((kind, LevelSnapshot::take(info, junctions, connections)));
+        snapshot.restore(info, junctions, connections);
+        self.open = None;
+        Some(kind)
+    }
+
+    // Forgetting everything, for when the level gets swapped out from somewhere else.
+    pub fn clear(&mut self) {
+        *self = EditHistory::default();
+    }
+}
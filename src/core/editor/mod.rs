@@ -0,0 +1,1491 @@
+use crate::components;
+use crate::core::editor::camera::{
+    apply_editor_view, camera_input, frame_input, reset_camera, EditorView,
+};
+use crate::core::editor::clipboard::{EditorClipboard, Subgraph};
+use crate::core::editor::history::{EditHistory, EditKind};
+use crate::core::editor::overlay::{refresh_issues, render_issues, LevelIssues};
+use crate::core::editor::palette::{paint_input, palette_input, JunctionBrush};
+use crate::core::editor::playtest::{
+    abandon_test_play, end_test_play, notice_replaced_level, run_test_play, test_play_input,
+    PlayTest,
+};
+use crate::core::editor::selection::{prune_selection, NodeDrag, NodeSelection};
+use crate::core::editor::snapping::{snapping_input, EditorSnapping};
+use crate::core::editor::symmetry::{
+    mirror_link, mirror_passages, symmetry_input, EditorSymmetry, MirrorAxis,
+};
+use crate::core::launch::LaunchOptions;
+use crate::core::level::{
+    ascii::import_ascii_file, file::LevelFile, generator::MazeSettings, graph, svg::save_svg,
+    templates::LevelTemplate, LevelPath, LevelReplaced,
+};
+use crate::core::prelude::*;
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+pub(super) struct EditorPlugin;
+
+pub mod camera; // Looking around.
+pub mod clipboard; // Copy, paste, repeat.
+pub mod history; // Taking things back.
+pub mod overlay; // Pointing out what's wrong, as it goes wrong.
+pub mod palette; // Picking what junctions turn into.
+pub mod playtest; // Trying it out, then picking up where we left off.
+pub mod selection; // Picking out more than one junction at a time.
+pub mod snapping; // Keeping things in line.
+pub mod symmetry; // Doing everything twice, backwards.
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct EditorGizmos {} // Storing all of our fancy lil' editor gizmos.
+
+#[derive(Resource)]
+pub struct PossibleConnection(pub Option<usize>); // Allowing for connections to be wired if need be. (Junction, ConnectionPoint)
+
+#[derive(Resource, Default)]
+struct LinkDrag(Option<(usize, Vec2)>); // Dragging from one junction to another to link them. (Junction, Cursor)
+
+#[derive(Resource)]
+pub struct NewLevelWizard {
+    pub template: LevelTemplate, // What we're starting from.
+    pub name: String,            // What the new level will be called.
+    pub size: Vec2,              // How much room the template gets to spread out in.
+}
+
+#[derive(Resource, Default)]
+pub struct HoveredJunction(pub Option<usize>); // Whichever junction the cursor's over.
+
+// The bits of level info that can be edited by hand.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum MetadataField {
+    Name,
+    Author,
+    Description,
+    ParTime,
+    AgentSpeed,
+    FrightenedDuration,
+    GhostRoster,
+    GhostTunnelSpeed,
+}
+
+impl MetadataField {
+    pub const ALL: [MetadataField; 8] = [
+        MetadataField::Name,
+        MetadataField::Author,
+        MetadataField::Description,
+        MetadataField::ParTime,
+        MetadataField::AgentSpeed,
+        MetadataField::FrightenedDuration,
+        MetadataField::GhostRoster,
+        MetadataField::GhostTunnelSpeed,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MetadataField::Name => "Name",
+            MetadataField::Author => "Author",
+            MetadataField::Description => "Description",
+            MetadataField::ParTime => "Par time",
+            MetadataField::AgentSpeed => "Agent speed",
+            MetadataField::FrightenedDuration => "Frightened for",
+            MetadataField::GhostRoster => "Ghost roster",
+            MetadataField::GhostTunnelSpeed => "Ghost tunnel speed",
+        }
+    }
+
+    fn step(&self, offset: isize) -> Self {
+        let index = MetadataField::ALL
+            .iter()
+            .position(|field| field == self)
+            .unwrap_or(0) as isize;
+        MetadataField::ALL[(index + offset).rem_euclid(MetadataField::ALL.len() as isize) as usize]
+    }
+
+    // The field as text, ready for editing. Empty means it's using the default.
+    pub fn read(&self, level_info: &components::LevelInfo) -> String {
+        let number = |value: Option<f32>| value.map(|value| value.to_string()).unwrap_or_default();
+        match self {
+            MetadataField::Name => level_info.name.clone(),
+            MetadataField::Author => level_info.author.clone(),
+            MetadataField::Description => level_info.description.clone(),
+            MetadataField::ParTime => number(level_info.par_time),
+            MetadataField::AgentSpeed => number(level_info.rules.agent_speed),
+            MetadataField::FrightenedDuration => number(level_info.rules.frightened_duration),
+            MetadataField::GhostRoster => level_info
+                .rules
+                .ghost_roster
+                .as_ref()
+                .map(|roster| {
+                    roster
+                        .iter()
+                        .map(|ghost| format!("{ghost:?}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default(),
+            MetadataField::GhostTunnelSpeed => number(level_info.rules.ghost_tunnel_speed),
+        }
+    }
+
+    // Putting edited text back into the level. Returns false (and leaves the level alone) if it doesn't make sense.
+    pub fn write(&self, level_info: &mut components::LevelInfo, text: &str) -> bool {
+        let number = |text: &str| -> Option<Option<f32>> {
+            if text.trim().is_empty() {
+                return Some(None); // Back to the default.
+            }
+            text.trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|value| *value > 0.0)
+                .map(Some)
+        };
+        match self {
+            MetadataField::Name => level_info.name = text.to_string(),
+            MetadataField::Author => level_info.author = text.to_string(),
+            MetadataField::Description => level_info.description = text.to_string(),
+            MetadataField::ParTime => match number(text) {
+                Some(value) => level_info.par_time = value,
+                None => return false,
+            },
+            MetadataField::AgentSpeed => match number(text) {
+                Some(value) => level_info.rules.agent_speed = value,
+                None => return false,
+            },
+            MetadataField::FrightenedDuration => match number(text) {
+                Some(value) => level_info.rules.frightened_duration = value,
+                None => return false,
+            },
+            MetadataField::GhostRoster => {
+                if text.trim().is_empty() {
+                    level_info.rules.ghost_roster = None;
+                    return true;
+                }
+                // The names are the same ones the level file uses, so let RON sort them out.
+                let roster: Option<Vec<components::NavAgentType>> = text
+                    .split(',')
+                    .map(|ghost| ron::from_str(ghost.trim()).ok())
+                    .collect();
+                match roster {
+                    Some(roster) => level_info.rules.ghost_roster = Some(roster),
+                    None => return false,
+                }
+            }
+            MetadataField::GhostTunnelSpeed => match number(text) {
+                Some(value) => level_info.rules.ghost_tunnel_speed = value,
+                None => return false,
+            },
+        }
+        true
+    }
+}
+
+#[derive(Resource)]
+pub struct MetadataEditor {
+    pub field: MetadataField, // Which field is being typed into.
+    pub buffer: String,       // What's been typed so far.
+    pub valid: bool,          // Whether the buffer made it into the level.
+}
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_gizmo_group::<EditorGizmos>()
+            .insert_resource(PossibleConnection(None))
+            .init_resource::<LinkDrag>()
+            .init_resource::<HoveredJunction>()
+            .insert_resource(NewLevelWizard {
+                template: LevelTemplate::Empty,
+                name: String::new(),
+                size: DEFAULT_LEVEL_SIZE,
+            })
+            .init_resource::<EditHistory>()
+            .init_resource::<NodeSelection>()
+            .init_resource::<EditorSnapping>()
+            .init_resource::<EditorClipboard>()
+            .init_resource::<EditorSymmetry>()
+            .init_resource::<EditorView>()
+            .init_resource::<PlayTest>()
+            .init_resource::<JunctionBrush>()
+            .init_resource::<LevelIssues>()
+            .insert_resource(MetadataEditor {
+                field: MetadataField::Name,
+                buffer: String::new(),
+                valid: true,
+            })
+            .add_systems(
+                Update,
+                (
+                    track_hover,
+                    render_editor,
+                    render_guides,
+                    (refresh_issues, render_issues).chain(),
+                )
+                    .run_if(in_editor),
+            )
+            .add_systems(
+                Update,
+                (camera_input, apply_editor_view).chain().run_if(in_editor),
+            )
+            .add_systems(
+                OnEnter(GameState::Playing(Playing::New)),
+                (reset_camera, run_test_play),
+            )
+            .add_systems(FixedUpdate, end_test_play.run_if(in_playing))
+            .add_systems(Update, notice_replaced_level.run_if(in_playing))
+            .add_systems(
+                Update,
+                abandon_test_play.run_if(in_editor.and_then(state_changed::<GameState>)),
+            )
+            .add_systems(Update, forget_replaced_level)
+            // Typing the name needs every key event, so this one doesn't get to be fixed.
+            .add_systems(
+                Update,
+                new_level_input.run_if(in_state(GameState::Editor(Editor::New))),
+            )
+            .add_systems(
+                Update,
+                metadata_input.run_if(in_state(GameState::Editor(Editor::Metadata))),
+            )
+            .add_systems(OnEnter(GameState::Editor(Editor::New)), start_new_level)
+            .add_systems(OnEnter(GameState::Editor(Editor::Metadata)), start_metadata)
+            .add_systems(OnEnter(GameState::Editor(Editor::Saving)), save_editor)
+            // One press, one go. Fixed ticks can land twice in a frame (or not at all), so hotkeys don't get to be fixed either.
+            .add_systems(
+                Update,
+                (
+                    test_play_input.run_if(
+                        in_state(GameState::Editor(Editor::Nodes))
+                            .or_else(in_state(GameState::Editor(Editor::Connections))),
+                    ),
+                    frame_input.run_if(
+                        in_state(GameState::Editor(Editor::Nodes))
+                            .or_else(in_state(GameState::Editor(Editor::Connections))),
+                    ),
+                    symmetry_input.run_if(
+                        in_state(GameState::Editor(Editor::Nodes))
+                            .or_else(in_state(GameState::Editor(Editor::Connections))),
+                    ),
+                    auto_connect_input.run_if(
+                        in_state(GameState::Editor(Editor::Nodes))
+                            .or_else(in_state(GameState::Editor(Editor::Connections))),
+                    ),
+                    straighten_input.run_if(
+                        in_state(GameState::Editor(Editor::Nodes))
+                            .or_else(in_state(GameState::Editor(Editor::Connections))),
+                    ),
+                    // The wizard's still building the level, and saving's busy with it.
+                    history_input.run_if(
+                        not(in_state(GameState::Editor(Editor::New)))
+                            .and_then(not(in_state(GameState::Editor(Editor::Saving)))),
+                    ),
+                    import_input,
+                    export_input,
+                    (
+                        prune_selection,
+                        snapping_input,
+                        palette_input,
+                        clipboard_input,
+                    )
+                        .chain()
+                        .run_if(in_state(GameState::Editor(Editor::Nodes))),
+                )
+                    .run_if(in_editor),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    manage_editors,
+                    (prune_selection, nodes_input, paint_input)
+                        .chain()
+                        .run_if(in_state(GameState::Editor(Editor::Nodes))),
+                    connections_input.run_if(in_state(GameState::Editor(Editor::Connections))),
+                )
+                    .run_if(in_editor),
+            );
+    }
+}
+
+// Keeping track of what's under the cursor, for anyone who wants to know.
+fn track_hover(
+    query_windows: Query<&Window, With<PrimaryWindow>>,
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+    junctions: Res<components::Junctions>,
+    mut hovered_junction: ResMut<HoveredJunction>,
+) {
+    let (camera, camera_transform) = query_camera.single();
+    let hovered = query_windows
+        .single()
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+        .map(|ray| ray.origin.truncate())
+        .and_then(|cursor_position| {
+            junctions
+                .0
+                .iter()
+                .position(|junction| junction.0.distance(cursor_position) < EDITOR_JUNCTION_RADIUS)
+        });
+    // Only touching it when it changes, so the HUD isn't redrawing for nothing.
+    if hovered_junction.0 != hovered {
+        hovered_junction.0 = hovered;
+    }
+}
+
+// Allows for swapping editors.
+fn manage_editors(
+    buttons: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Ctrl+S to save, Ctrl+N to start over, from whichever editor we're in.
+    if buttons.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        if buttons.just_pressed(KeyCode::KeyS) {
+            next_state.set(GameState::Editor(Editor::Saving));
+            return;
+        }
+        if buttons.just_pressed(KeyCode::KeyN) {
+            next_state.set(GameState::Editor(Editor::New));
+            return;
+        }
+    }
+
+    if buttons.just_pressed(KeyCode::Tab) {
+        match state.get() {
+            GameState::Menu(_menu) => unimplemented!(),
+            GameState::Editor(editor) => match editor {
+                Editor::New => next_state.set(GameState::Editor(Editor::Nodes)),
+                Editor::Nodes => next_state.set(GameState::Editor(Editor::Connections)),
+                Editor::Connections => next_state.set(GameState::Editor(Editor::Metadata)),
+                Editor::Metadata => next_state.set(GameState::Editor(Editor::Nodes)),
+                Editor::Saving => {} // Hold on, we're busy writing.
+            },
+            GameState::Playing(_playing) => unimplemented!(),
+        }
+    }
+}
+
+// Ctrl+Z to undo, Ctrl+Shift+Z to redo.
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+fn history_input(
+    buttons: Res<ButtonInput<KeyCode>>,
+    mut history: ResMut<EditHistory>,
+    mut level_info: ResMut<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut possible_connection: ResMut<PossibleConnection>,
+    mut link_drag: ResMut<LinkDrag>,
+    mut selection: ResMut<NodeSelection>,
+    mut metadata_editor: ResMut<MetadataEditor>,
+) {
+    if !(buttons.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        && buttons.just_pressed(KeyCode::KeyZ))
+    {
+        return;
+    }
+    let redoing = buttons.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let edit = if redoing {
+        history.redo(&mut level_info, &mut junctions, &mut connections)
+    } else {
+        history.undo(&mut level_info, &mut junctions, &mut connections)
+    };
+
+    match edit {
+        Some(edit) => {
+            possible_connection.0 = None; // That slot might not even exist anymore.
+                                          // Same goes for whatever's being dragged around. Let go, and grab it again if it's still there.
+            link_drag.0 = None;
+            selection.drag = None;
+            selection.twins.clear();
+            metadata_editor.buffer = metadata_editor.field.read(&level_info);
+            metadata_editor.valid = true;
+            info!("{} {edit:?}.", if redoing { "Redid" } else { "Undid" });
+        }
+        None => info!("Nothing to {}.", if redoing { "redo" } else { "undo" }),
+    }
+}
+
+// The level got swapped out from under us, so whatever was selected might not exist anymore.
+fn forget_replaced_level(
+    mut level_replaced: EventReader<LevelReplaced>,
+    mut possible_connection: ResMut<PossibleConnection>,
+    level_info: Res<components::LevelInfo>,
+    mut metadata_editor: ResMut<MetadataEditor>,
+    mut history: ResMut<EditHistory>,
+    mut selection: ResMut<NodeSelection>,
+    mut link_drag: ResMut<LinkDrag>,
+) {
+    if level_replaced.read().count() > 0 {
+        possible_connection.0 = None;
+        link_drag.0 = None;
+        selection.clear();
+        history.clear(); // None of it applies to the new level.
+        metadata_editor.buffer = metadata_editor.field.read(&level_info); // Whatever was half typed belonged to the old level.
+        metadata_editor.valid = true;
+    }
+}
+
+// Picking up wherever the level info's at.
+fn start_metadata(
+    level_info: Res<components::LevelInfo>,
+    mut metadata_editor: ResMut<MetadataEditor>,
+) {
+    metadata_editor.buffer = metadata_editor.field.read(&level_info);
+    metadata_editor.valid = true;
+}
+
+// Up and down (or enter) to pick a field, then type away.
+fn metadata_input(
+    buttons: Res<ButtonInput<KeyCode>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut metadata_editor: ResMut<MetadataEditor>,
+    mut level_info: ResMut<components::LevelInfo>,
+    junctions: Res<components::Junctions>,
+    connections: Res<components::Connections>,
+    mut history: ResMut<EditHistory>,
+) {
+    let offset = if buttons.just_pressed(KeyCode::ArrowUp) {
+        -1
+    } else if buttons.any_just_pressed([KeyCode::ArrowDown, KeyCode::Enter]) {
+        1
+    } else {
+        0
+    };
+    if offset != 0 {
+        metadata_editor.field = metadata_editor.field.step(offset);
+        history.settle(); // The next field gets its own undo step.
+        metadata_editor.buffer = metadata_editor.field.read(&level_info);
+        metadata_editor.valid = true;
+        keyboard_events.clear();
+        return;
+    }
+
+    let mut changed = false;
+    for keyboard_event in keyboard_events.read() {
+        if !keyboard_event.state.is_pressed() {
+            continue;
+        }
+        match &keyboard_event.logical_key {
+            // Ctrl's for shortcuts, not for typing.
+            Key::Character(_)
+                if buttons.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) => {}
+            Key::Character(character) => {
+                metadata_editor.buffer.extend(
+                    character
+                        .chars()
+                        .filter(|character| !character.is_control()),
+                );
+                changed = true;
+            }
+            Key::Space => {
+                metadata_editor.buffer.push(' ');
+                changed = true;
+            }
+            Key::Backspace => {
+                metadata_editor.buffer.pop();
+                changed = true;
+            }
+            _ => {}
+        }
+    }
+
+    if changed {
+        let before = level_info.clone();
+        let valid = metadata_editor
+            .field
+            .write(&mut level_info, &metadata_editor.buffer);
+        metadata_editor.valid = valid;
+        if *level_info != before {
+            history.record(
+                EditKind::Metadata(metadata_editor.field),
+                &before,
+                &junctions,
+                &connections,
+            );
+        }
+    }
+}
+
+// Ctrl+I to pull in a tile maze, replacing whatever's here.
+fn import_input(
+    buttons: Res<ButtonInput<KeyCode>>,
+    launch_options: Res<LaunchOptions>,
+    level_info: Res<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut possible_connection: ResMut<PossibleConnection>,
+    mut history: ResMut<EditHistory>,
+) {
+    if !(buttons.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        && buttons.just_pressed(KeyCode::KeyI))
+    {
+        return;
+    }
+
+    // Whatever maze was named on the command line, or the usual one.
+    let ascii_path = launch_options
+        .import_ascii
+        .clone()
+        .unwrap_or(DEFAULT_ASCII_PATH.into());
+    match import_ascii_file(&ascii_path, ASCII_TILE_SIZE) {
+        Ok(level) => {
+            history.record(EditKind::ImportMaze, &level_info, &junctions, &connections);
+            (*junctions, *connections) = level;
+            possible_connection.0 = None; // That slot might not even exist anymore.
+            info!("Imported maze from {}.", ascii_path.display());
+        }
+        Err(error) => error!("Couldn't import {}: {error}", ascii_path.display()),
+    }
+}
+
+// Ctrl+E to draw the level out as an SVG, right next to the level file.
+fn export_input(
+    buttons: Res<ButtonInput<KeyCode>>,
+    level_path: Res<LevelPath>,
+    junctions: Res<components::Junctions>,
+    connections: Res<components::Connections>,
+) {
+    if !(buttons.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        && buttons.just_pressed(KeyCode::KeyE))
+    {
+        return;
+    }
+
+    let svg_path = level_path.0.with_extension("svg");
+    match save_svg(&svg_path, &junctions, &connections, true) {
+        Ok(()) => info!("Exported level to {}.", svg_path.display()),
+        Err(error) => error!("Couldn't export {}: {error}", svg_path.display()),
+    }
+}
+
+// Writing the level out to disk, then heading back to the editor.
+fn save_editor(
+    level_path: Res<LevelPath>,
+    level_info: Res<components::LevelInfo>,
+    junctions: Res<components::Junctions>,
+    connections: Res<components::Connections>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    match LevelFile::from_level(&level_info, &junctions, &connections).save(&level_path.0) {
+        Ok(()) => info!("Saved level to {}.", level_path.0.display()),
+        Err(error) => error!("Couldn't save {}: {error}", level_path.0.display()),
+    }
+
+    next_state.set(GameState::Editor(Editor::Nodes));
+}
+
+// Wiping the slate clean for a new level.
+fn start_new_level(
+    mut wizard: ResMut<NewLevelWizard>,
+    mut level_info: ResMut<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut possible_connection: ResMut<PossibleConnection>,
+    mut history: ResMut<EditHistory>,
+    mut selection: ResMut<NodeSelection>,
+) {
+    // The wizard can fiddle with the new level all it likes, it's one step to get back to the old one.
+    history.record(EditKind::NewLevel, &level_info, &junctions, &connections);
+    wizard.name.clear();
+    possible_connection.0 = None; // Whatever was selected is long gone.
+    selection.clear();
+    build_new_level(&wizard, &mut level_info, &mut junctions, &mut connections);
+}
+
+// Picking a template, naming it, and sizing it up.
+fn new_level_input(
+    buttons: Res<ButtonInput<KeyCode>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut wizard: ResMut<NewLevelWizard>,
+    mut level_info: ResMut<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let mut changed = false;
+
+    // Left and right to flip through templates.
+    if buttons.just_pressed(KeyCode::ArrowRight) {
+        wizard.template = wizard.template.next();
+        changed = true;
+    }
+    if buttons.just_pressed(KeyCode::ArrowLeft) {
+        wizard.template = wizard.template.previous();
+        changed = true;
+    }
+
+    // Up and down for rows, page up and page down for columns. Only grids and generated mazes care.
+    // Generated mazes also take Home and End for more or fewer loops, F5 for a new seed and F6 to flip mirroring.
+    let template = match wizard.template {
+        LevelTemplate::Grid(columns, rows) => {
+            let (columns, rows) = resize_grid(&buttons, columns, rows, 1);
+            LevelTemplate::Grid(columns, rows)
+        }
+        LevelTemplate::Generated(settings) => {
+            // Mirrored mazes always get a middle column, so step over the even ones.
+            let (columns, rows) = resize_grid(
+                &buttons,
+                settings.columns,
+                settings.rows,
+                if settings.mirrored { 2 } else { 1 },
+            );
+            let density = if buttons.just_pressed(KeyCode::Home) {
+                (settings.density + 10).min(100)
+            } else if buttons.just_pressed(KeyCode::End) {
+                settings.density.saturating_sub(10)
+            } else {
+                settings.density
+            };
+            LevelTemplate::Generated(MazeSettings {
+                seed: if buttons.just_pressed(KeyCode::F5) {
+                    rand::random()
+                } else {
+                    settings.seed
+                },
+                columns,
+                rows,
+                density,
+                mirrored: settings.mirrored != buttons.just_pressed(KeyCode::F6),
+            })
+        }
+        template => template,
+    };
+    if wizard.template != template {
+        wizard.template = template;
+        changed = true;
+    }
+
+    // Plus and minus to grow or shrink the whole thing.
+    if buttons.just_pressed(KeyCode::Equal) {
+        wizard.size *= 1.1;
+        changed = true;
+    }
+    if buttons.just_pressed(KeyCode::Minus) {
+        wizard.size /= 1.1;
+        changed = true;
+    }
+
+    // Anything typed goes into the name.
+    for keyboard_event in keyboard_events.read() {
+        if !keyboard_event.state.is_pressed() {
+            continue;
+        }
+        match &keyboard_event.logical_key {
+            // Ctrl's for shortcuts, and = and - are busy resizing, so none of those get typed.
+            Key::Character(_)
+                if buttons.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+                    || matches!(keyboard_event.key_code, KeyCode::Equal | KeyCode::Minus) => {}
+            Key::Character(character) => {
+                wizard.name.extend(
+                    character
+                        .chars()
+                        .filter(|character| !character.is_control()),
+                );
+                changed = true;
+            }
+            Key::Space => {
+                wizard.name.push(' ');
+                changed = true;
+            }
+            Key::Backspace => {
+                wizard.name.pop();
+                changed = true;
+            }
+            _ => {}
+        }
+    }
+
+    if changed {
+        build_new_level(&wizard, &mut level_info, &mut junctions, &mut connections);
+    }
+
+    // Enter to get to work on it.
+    if buttons.just_pressed(KeyCode::Enter) {
+        next_state.set(GameState::Editor(Editor::Nodes));
+    }
+}
+
+// Up and down for rows, page up and page down for columns, never going below one of each.
+fn resize_grid(
+    buttons: &ButtonInput<KeyCode>,
+    columns: usize,
+    rows: usize,
+    column_step: usize,
+) -> (usize, usize) {
+    let rows = if buttons.just_pressed(KeyCode::ArrowUp) {
+        rows + 1
+    } else if buttons.just_pressed(KeyCode::ArrowDown) {
+        rows.saturating_sub(1).max(1)
+    } else {
+        rows
+    };
+    let columns = if buttons.just_pressed(KeyCode::PageUp) {
+        columns + column_step
+    } else if buttons.just_pressed(KeyCode::PageDown) {
+        columns.saturating_sub(column_step).max(1)
+    } else {
+        columns
+    };
+    (columns, rows)
+}
+
+// Swapping the current level out for whatever the wizard has set up.
+fn build_new_level(
+    wizard: &NewLevelWizard,
+    level_info: &mut components::LevelInfo,
+    junctions: &mut components::Junctions,
+    connections: &mut components::Connections,
+) {
+    (*junctions, *connections) = wizard.template.build(wizard.size);
+    *level_info = components::LevelInfo {
+        name: if wizard.name.is_empty() {
+            wizard.template.name().to_string()
+        } else {
+            wizard.name.clone()
+        },
+        size: wizard.size,
+        ..default()
+    };
+    info!(
+        "New level: \"{}\" from {:?}, {}x{}.",
+        level_info.name, wizard.template, wizard.size.x, wizard.size.y
+    );
+}
+
+// A to auto-connect whatever's picked (the selected slot's junction, or the selected junctions). Shift+A for the whole level.
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+fn auto_connect_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    level_info: Res<components::LevelInfo>,
+    junctions: Res<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut history: ResMut<EditHistory>,
+    selection: Res<NodeSelection>,
+    mut possible_connection: ResMut<PossibleConnection>,
+    symmetry: Res<EditorSymmetry>,
+) {
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        || !keys.just_pressed(KeyCode::KeyA)
+    {
+        return;
+    }
+
+    let from: Vec<usize> = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        (0..junctions.0.len()).collect()
+    } else if let Some(junction) = possible_connection
+        .0
+        .map(|slot| slot / MAX_CONNECTIONS)
+        .filter(|junction| *junction < junctions.0.len())
+    {
+        vec![junction]
+    } else {
+        selection.junctions.clone()
+    };
+    if from.is_empty() {
+        info!("Nothing picked to auto-connect. Shift+A does the whole level.");
+        return;
+    }
+
+    let before = connections.clone();
+    let made = graph::auto_connect(&junctions, &mut connections, &from);
+    if let Some(axis) = symmetry.axis {
+        for (first_slot, second_slot) in &made {
+            mirror_link(
+                axis,
+                &junctions,
+                &mut connections,
+                *first_slot,
+                *second_slot,
+            );
+        }
+    }
+    if connections.0 != before.0 {
+        history.record(EditKind::AutoConnect, &level_info, &junctions, &before);
+    }
+    possible_connection.0 = None;
+    info!("Auto-connected {} links.", made.len());
+}
+
+// L to straighten out the links around the picked junctions (or the selected slot's junction). Shift+L for the whole level.
+fn straighten_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    level_info: Res<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    connections: Res<components::Connections>,
+    mut history: ResMut<EditHistory>,
+    selection: Res<NodeSelection>,
+    possible_connection: Res<PossibleConnection>,
+) {
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        || !keys.just_pressed(KeyCode::KeyL)
+    {
+        return;
+    }
+
+    let movable: Vec<usize> = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        (0..junctions.0.len()).collect()
+    } else if let Some(junction) = possible_connection
+        .0
+        .map(|slot| slot / MAX_CONNECTIONS)
+        .filter(|junction| *junction < junctions.0.len())
+    {
+        vec![junction]
+    } else {
+        selection.junctions.clone()
+    };
+    if movable.is_empty() {
+        info!("Nothing picked to straighten. Shift+L does the whole level.");
+        return;
+    }
+
+    let before = components::Junctions(junctions.0.clone());
+    let moved = graph::straighten_links(&mut junctions, &connections, &movable);
+    if junctions.0 != before.0 {
+        history.record(EditKind::Straighten, &level_info, &before, &connections);
+    }
+    info!(
+        "Straightened {} junctions, moving them {moved:.0} all told.",
+        movable.len()
+    );
+}
+
+// Ctrl+C to copy the selection, Ctrl+V to paste it at the cursor, Ctrl+D to duplicate it on the spot.
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+fn clipboard_input(
+    query_windows: Query<&Window, With<PrimaryWindow>>,
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+    keys: Res<ButtonInput<KeyCode>>,
+    level_info: Res<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut history: ResMut<EditHistory>,
+    mut selection: ResMut<NodeSelection>,
+    mut snapping: ResMut<EditorSnapping>,
+    mut clipboard: ResMut<EditorClipboard>,
+) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyC) {
+        if let Some(subgraph) = Subgraph::copy(&junctions, &connections, &selection.junctions) {
+            info!(
+                "Copied {} junctions and {} links.",
+                subgraph.junctions.len(),
+                subgraph.links.len()
+            );
+            clipboard.0 = Some(subgraph);
+        }
+    }
+
+    // Pasting the clipboard where the cursor is, or where it came from if the cursor's wandered off.
+    let pasting = if keys.just_pressed(KeyCode::KeyV) {
+        clipboard.0.clone().map(|subgraph| {
+            let (camera, camera_transform) = query_camera.single();
+            let position = query_windows
+                .single()
+                .cursor_position()
+                .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+                .map(|ray| snapping.snap(ray.origin.truncate(), &junctions, &[]))
+                .unwrap_or(subgraph.origin);
+            (subgraph, position)
+        })
+    // Duplicating skips the clipboard, and lands just off to the side.
+    } else if keys.just_pressed(KeyCode::KeyD) {
+        Subgraph::copy(&junctions, &connections, &selection.junctions).map(|subgraph| {
+            let position = subgraph.origin + Vec2::new(1.0, -1.0) * EDITOR_JUNCTION_RADIUS * 2.0;
+            (subgraph, position)
+        })
+    } else {
+        None
+    };
+
+    if let Some((subgraph, position)) = pasting {
+        history.record(EditKind::Paste, &level_info, &junctions, &connections);
+        // The fresh copy gets picked, so it can be dragged straight into place.
+        selection.junctions = subgraph.paste(&mut junctions, &mut connections, position);
+        selection.drag = None;
+    }
+}
+
+// Editor input.
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+fn nodes_input(
+    query_windows: Query<&Window, With<PrimaryWindow>>,
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    level_info: Res<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut history: ResMut<EditHistory>,
+    mut selection: ResMut<NodeSelection>,
+    mut snapping: ResMut<EditorSnapping>,
+    mut possible_connection: ResMut<PossibleConnection>,
+    symmetry: Res<EditorSymmetry>,
+    brush: Res<JunctionBrush>,
+) {
+    snapping.active_guides.clear(); // Only showing them while they're in use.
+
+    // get the camera info and transform
+    // assuming there is exactly one main camera entity, so Query::single() is OK
+    let (camera, camera_transform) = query_camera.single();
+
+    let cursor_position = query_windows
+        .single()
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+        .map(|ray| ray.origin.truncate());
+    let under_cursor = cursor_position.and_then(|cursor_position| {
+        junctions.0.iter().position(|junction| {
+            (junction.0.x - cursor_position.x).powf(2.0)
+                + (junction.0.y - cursor_position.y).powf(2.0)
+                < EDITOR_JUNCTION_RADIUS.powf(2.0)
+        })
+    });
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    // Starting something with LMB. (Unless Space is down, that's for panning.)
+    if buttons.just_pressed(MouseButton::Left) && !keys.pressed(KeyCode::Space) {
+        if let Some(cursor_position) = cursor_position {
+            match under_cursor {
+                // Shift-click to pick junctions out one at a time.
+                Some(junction) if shift => selection.toggle(junction),
+                // Grabbing a junction grabs the whole selection with it.
+                Some(junction) => {
+                    if !selection.contains(junction) {
+                        selection.junctions = vec![junction];
+                    }
+                    // Working out the twins up front, so they stay paired even when things pass over the axis.
+                    selection.twins = match symmetry.axis {
+                        Some(axis) => selection
+                            .junctions
+                            .iter()
+                            .filter_map(|junction| axis.other_twin(&junctions, *junction))
+                            .filter(|twin| !selection.contains(*twin))
+                            .collect(),
+                        None => Vec::new(),
+                    };
+                    selection.drag = Some(NodeDrag::Moving(
+                        junction,
+                        junctions.0[junction].0 - cursor_position,
+                    ));
+                }
+                // Nothing here, so start boxing.
+                None => selection.drag = Some(NodeDrag::Boxing(cursor_position, cursor_position)),
+            }
+        }
+    }
+
+    // Keeping it going. (Moving junctions.)
+    if buttons.pressed(MouseButton::Left) {
+        if let Some(cursor_position) = cursor_position {
+            match selection.drag {
+                Some(NodeDrag::Moving(grabbed, offset)) => {
+                    // The grabbed junction gets lined up, and everything else just follows along.
+                    let moving =
+                        [selection.junctions.as_slice(), selection.twins.as_slice()].concat();
+                    let target = snapping.snap(cursor_position + offset, &junctions, &moving);
+                    let delta = target - junctions.0[grabbed].0;
+                    if delta != Vec2::ZERO {
+                        // Move iiit. The whole drag comes undone in one go.
+                        history.record(
+                            EditKind::MoveJunctions,
+                            &level_info,
+                            &junctions,
+                            &connections,
+                        );
+                        for junction in &selection.junctions {
+                            junctions.0[*junction].0 += delta;
+                        }
+                        // And the other side goes the other way.
+                        if let Some(axis) = symmetry.axis {
+                            for twin in &selection.twins {
+                                junctions.0[*twin].0 += axis.mirror_vector(delta);
+                            }
+                        }
+                    }
+                }
+                Some(NodeDrag::Boxing(start, _)) => {
+                    selection.drag = Some(NodeDrag::Boxing(start, cursor_position));
+                }
+                _ => {}
+            }
+        }
+    } else if let Some(drag) = selection.drag.take() {
+        // And finishing it.
+        history.settle(); // Drag's over.
+        selection.twins.clear();
+        if let NodeDrag::Boxing(start, end) = drag {
+            if start.distance(end) < EDITOR_JUNCTION_CONNECTION_POINT_RADIUS {
+                // Barely moved, so it was just a click on empty space.
+                if !shift {
+                    // Creating our junction.
+                    let position = snapping.snap(start, &junctions, &[]);
+                    history.record(EditKind::AddJunction, &level_info, &junctions, &connections);
+                    // Our new junction, fresh off the brush. And its twin, unless it's sitting right on the axis.
+                    graph::add_junction(&mut junctions, &mut connections, position, brush.0);
+                    if let Some(axis) = symmetry.axis.filter(|axis| !axis.on_axis(position)) {
+                        graph::add_junction(
+                            &mut junctions,
+                            &mut connections,
+                            axis.mirror_point(position),
+                            brush.0,
+                        );
+                    }
+                    selection.junctions.clear();
+                }
+            } else {
+                // Shift keeps what's already picked.
+                if !shift {
+                    selection.junctions.clear();
+                }
+                selection.add_box(&junctions, start, end);
+            }
+        }
+    }
+
+    // Deleting junctions. via RMB on one, or Delete for the whole selection.
+    let doomed = if buttons.just_pressed(MouseButton::Right) {
+        match under_cursor {
+            // Right-clicking anything selected takes the lot.
+            Some(junction) if selection.contains(junction) => selection.junctions.clone(),
+            Some(junction) => vec![junction],
+            None => Vec::new(),
+        }
+    } else if keys.just_pressed(KeyCode::Delete) {
+        selection.junctions.clone()
+    } else {
+        Vec::new()
+    };
+    if !doomed.is_empty() {
+        history.record(
+            EditKind::DeleteJunctions,
+            &level_info,
+            &junctions,
+            &connections,
+        );
+        let mut doomed = doomed;
+        // Taking the twins down too.
+        if let Some(axis) = symmetry.axis {
+            let twins: Vec<usize> = doomed
+                .iter()
+                .filter_map(|junction| axis.other_twin(&junctions, *junction))
+                .collect();
+            doomed.extend(twins);
+        }
+        doomed.sort_unstable();
+        doomed.dedup();
+        // Back to front, so the indices we've still got to go don't shift.
+        for junction in doomed.into_iter().rev() {
+            graph::remove_junction(&mut junctions, &mut connections, junction);
+        }
+        selection.clear(); // Everything's shuffled around.
+        possible_connection.0 = None; // The slot picked over in Connections might've gone with them.
+    }
+
+    // Escape to let go of everything.
+    if keys.just_pressed(KeyCode::Escape) {
+        selection.junctions.clear();
+    }
+}
+
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+fn connections_input(
+    query_windows: Query<&Window, With<PrimaryWindow>>,
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    level_info: Res<components::LevelInfo>,
+    junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut possible_connection: ResMut<PossibleConnection>,
+    mut history: ResMut<EditHistory>,
+    symmetry: Res<EditorSymmetry>,
+    mut link_drag: ResMut<LinkDrag>,
+) {
+    // Same camera thing.
+    let (camera, camera_transform) = query_camera.single();
+
+    // Dragging from the middle of one junction to another links them through whichever slots face each other.
+    let cursor_position = query_windows
+        .single()
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+        .map(|ray| ray.origin.truncate());
+    let under_cursor = cursor_position.and_then(|cursor_position| {
+        junctions.0.iter().position(|junction| {
+            junction.0.distance(cursor_position)
+                < EDITOR_JUNCTION_RADIUS - EDITOR_JUNCTION_CONNECTION_POINT_RADIUS
+        })
+    });
+    if let Some((from, _)) = link_drag.0 {
+        if buttons.pressed(MouseButton::Left) {
+            if let Some(cursor_position) = cursor_position {
+                link_drag.0 = Some((from, cursor_position));
+            }
+        } else {
+            link_drag.0 = None;
+            if let Some(to) = under_cursor.filter(|to| *to != from) {
+                let before = connections.clone();
+                match graph::link_junctions(&junctions, &mut connections, from, to) {
+                    Some((first_slot, second_slot)) => {
+                        if let Some(axis) = symmetry.axis {
+                            mirror_link(
+                                axis,
+                                &junctions,
+                                &mut connections,
+                                first_slot,
+                                second_slot,
+                            );
+                        }
+                        history.record(EditKind::Link, &level_info, &junctions, &before);
+                    }
+                    None => info!("The slots facing each other are already taken."),
+                }
+            }
+        }
+    }
+
+    // Creating them connections. (Unless Space is down, that's for panning.)
+    if buttons.just_pressed(MouseButton::Left) && !keys.pressed(KeyCode::Space) {
+        // Finding our cursor.
+        if let Some(cursor_position) = query_windows
+            .single()
+            .cursor_position()
+            .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+            .map(|ray| ray.origin.truncate())
+        {
+            // Finding a node.
+            if let Some(junction) = junctions.0.iter().position(|junction| {
+                (junction.0.x - cursor_position.x).powf(2.0)
+                    + (junction.0.y - cursor_position.y).powf(2.0)
+                    < (EDITOR_JUNCTION_RADIUS + EDITOR_JUNCTION_CONNECTION_POINT_RADIUS).powf(2.0)
+            }) {
+                // *Or* finding a viable slot to put the connection in.
+                if let Some(connection_point) = (0..MAX_CONNECTIONS).find(|connection_point| {
+                    let junction_start_pos =
+                        graph::slot_position(junctions.0[junction].0, *connection_point);
+                    (junction_start_pos.x - cursor_position.x).powf(2.0)
+                        + (junction_start_pos.y - cursor_position.y).powf(2.0)
+                        < EDITOR_JUNCTION_CONNECTION_POINT_RADIUS.powf(2.0)
+                }) {
+                    // We found the node!
+                    // If there's nothing at this node.
+                    if connections.0[junction * MAX_CONNECTIONS + connection_point].is_none() {
+                        // Check to see if it has anything. (That's still there, and still free.)
+                        match possible_connection.0.filter(|value| {
+                            value / MAX_CONNECTIONS < junctions.0.len()
+                                && connections.0.get(*value) == Some(&None)
+                        }) {
+                            Some(value) => {
+                                // If it's not the same as our existing connection.
+                                if value != junction * MAX_CONNECTIONS + connection_point {
+                                    history.record(
+                                        EditKind::Link,
+                                        &level_info,
+                                        &junctions,
+                                        &connections,
+                                    );
+                                    graph::link_slots(
+                                        &mut connections,
+                                        value,
+                                        junction * MAX_CONNECTIONS + connection_point,
+                                    ); // Set the value, and the same the other way around.
+                                    possible_connection.0 = None; // Reset our possible connections.
+
+                                    // Wiring up the twins too, through the mirrored slots.
+                                    if let Some(axis) = symmetry.axis {
+                                        mirror_link(
+                                            axis,
+                                            &junctions,
+                                            &mut connections,
+                                            value,
+                                            junction * MAX_CONNECTIONS + connection_point,
+                                        );
+                                    }
+                                }
+                            }
+                            None => {
+                                // We have no existing connection.
+                                possible_connection.0 =
+                                    Some(junction * MAX_CONNECTIONS + connection_point);
+                                // Set the possible connection to the current one.
+                            }
+                        }
+                    }
+                } else if under_cursor == Some(junction) {
+                    // Not on a slot, but right in the middle of the junction. Start dragging out a link.
+                    possible_connection.0 = None;
+                    link_drag.0 = Some((junction, cursor_position));
+                }
+            } else {
+                // Oh, we didn't hit a node?
+                possible_connection.0 = None; // Get rid of the selection, since they probably didn't want it.
+            }
+        }
+    }
+
+    // Restricting them connections. R flips through who can leave by the slot under the cursor, O makes its link one-way out of it.
+    // T turns its link into a tunnel, or back into a corridor.
+    let restrict = keys.just_pressed(KeyCode::KeyR);
+    let one_way = keys.just_pressed(KeyCode::KeyO);
+    let tunnel = keys.just_pressed(KeyCode::KeyT);
+    if (restrict || one_way || tunnel)
+        && !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    {
+        let hovered_slot = cursor_position.and_then(|cursor_position| {
+            (0..connections.0.len()).find(|slot| {
+                graph::slot_position(junctions.0[slot / MAX_CONNECTIONS].0, *slot)
+                    .distance(cursor_position)
+                    < EDITOR_JUNCTION_CONNECTION_POINT_RADIUS
+            })
+        });
+        match hovered_slot.and_then(|slot| Some((slot, connections.0[slot]?))) {
+            Some((slot, other_slot)) if tunnel => {
+                history.record(EditKind::Tunnel, &level_info, &junctions, &connections);
+                let kind = match connections.1[slot].kind {
+                    components::PassageKind::Corridor => components::PassageKind::Tunnel,
+                    components::PassageKind::Tunnel => components::PassageKind::Corridor,
+                };
+                connections.1[slot].kind = kind;
+                connections.1[other_slot].kind = kind;
+                if let Some(axis) = symmetry.axis {
+                    mirror_passages(axis, &junctions, &mut connections, slot);
+                }
+                info!(
+                    "Junction {} slot {} is a {kind:?} now.",
+                    slot / MAX_CONNECTIONS,
+                    slot % MAX_CONNECTIONS
+                );
+            }
+            Some((slot, other_slot)) => {
+                history.record(EditKind::Restrict, &level_info, &junctions, &connections);
+                let (allowed, other_allowed) = if restrict {
+                    (
+                        connections.1[slot].allowed.next(),
+                        connections.1[other_slot].allowed,
+                    )
+                } else if connections.1[slot].allowed == components::AgentMask::ALL
+                    && connections.1[other_slot].allowed == components::AgentMask::NONE
+                {
+                    (components::AgentMask::ALL, components::AgentMask::ALL) // Already one-way, so back to both ways.
+                } else {
+                    (components::AgentMask::ALL, components::AgentMask::NONE)
+                };
+                connections.1[slot].allowed = allowed;
+                connections.1[other_slot].allowed = other_allowed;
+                if let Some(axis) = symmetry.axis {
+                    mirror_passages(axis, &junctions, &mut connections, slot);
+                }
+                info!(
+                    "Junction {} slot {} lets out {:?}, and back in {:?}.",
+                    slot / MAX_CONNECTIONS,
+                    slot % MAX_CONNECTIONS,
+                    allowed,
+                    other_allowed
+                );
+            }
+            None => info!("Point at a linked slot to change it."),
+        }
+    }
+
+    // Deleting them connections.
+    if buttons.just_pressed(MouseButton::Right) {
+        possible_connection.0 = None; // Tossing the current selection, 'cause I'm assuming they didn't want it.
+
+        // Finding our cursor.
+        if let Some(cursor_position) = query_windows
+            .single()
+            .cursor_position()
+            .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+            .map(|ray| ray.origin.truncate())
+        {
+            // Finding a valid junction.
+            if let Some(junction) = junctions.0.iter().position(|junction| {
+                (junction.0.x - cursor_position.x).powf(2.0)
+                    + (junction.0.y - cursor_position.y).powf(2.0)
+                    < EDITOR_JUNCTION_RADIUS.powf(2.0)
+            }) {
+                // Junction found! Time to iterate. (Ctrl+Z if that was a mistake.)
+                // Its twin loses its connections too.
+                let twin = symmetry
+                    .axis
+                    .and_then(|axis| axis.other_twin(&junctions, junction));
+                if std::iter::once(junction).chain(twin).any(|junction| {
+                    connections.0[junction * MAX_CONNECTIONS..(junction + 1) * MAX_CONNECTIONS]
+                        .iter()
+                        .any(|slot| slot.is_some())
+                }) {
+                    history.record(EditKind::Unlink, &level_info, &junctions, &connections);
+                }
+                for junction in std::iter::once(junction).chain(twin) {
+                    for our_connection in 0..MAX_CONNECTIONS {
+                        // Get rid of us, and the straggler on the other end.
+                        graph::unlink_slot(
+                            &mut connections,
+                            junction * MAX_CONNECTIONS + our_connection,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Rendering the current state of the editor.
+fn render_editor(
+    mut editor_gizmos: Gizmos<EditorGizmos>,
+    junctions: Res<components::Junctions>,
+    connections: Res<components::Connections>,
+    possible_connection: Res<PossibleConnection>,
+    selection: Res<NodeSelection>,
+    link_drag: Res<LinkDrag>,
+) {
+    // The link being dragged out.
+    if let Some((from, cursor_position)) = link_drag.0 {
+        if let Some(from) = junctions.0.get(from) {
+            editor_gizmos.line_2d(from.0, cursor_position, bevy::color::palettes::css::GREEN);
+        }
+    }
+    // Whatever's been picked out, with a bit of a glow.
+    for junction in &selection.junctions {
+        if let Some(junction) = junctions.0.get(*junction) {
+            editor_gizmos.circle_2d(
+                junction.0,
+                EDITOR_JUNCTION_RADIUS + EDITOR_JUNCTION_CONNECTION_POINT_RADIUS * 1.5,
+                bevy::color::palettes::css::YELLOW,
+            );
+        }
+    }
+    // And the box being dragged out.
+    if let Some(NodeDrag::Boxing(start, end)) = selection.drag {
+        editor_gizmos.rect_2d(
+            (start + end) * 0.5,
+            0.0,
+            (end - start).abs(),
+            bevy::color::palettes::css::YELLOW,
+        );
+    }
+
+    // For each of the junctions we have.
+    for (junction_index, junction) in junctions.0.iter().enumerate() {
+        // For each of the eight possible connections this junction could have.
+        for possible_connection_index in 0..MAX_CONNECTIONS {
+            let junction_start_pos = graph::slot_position(junction.0, possible_connection_index);
+            // If an index exists.
+            if let Some(found_junction_index) =
+                connections.0[junction_index * MAX_CONNECTIONS + possible_connection_index]
+            {
+                let slot = junction_index * MAX_CONNECTIONS + possible_connection_index;
+                let passage = connections.1[slot];
+                // Get that position! Corridors go halfway there, tunnels just poke out the side.
+                let junction_end_pos = match passage.kind {
+                    components::PassageKind::Corridor => {
+                        (graph::slot_position(
+                            junctions.0[found_junction_index / MAX_CONNECTIONS].0,
+                            found_junction_index,
+                        ) + junction_start_pos)
+                            * 0.5
+                    }
+                    components::PassageKind::Tunnel => {
+                        let mouth = junction.0
+                            + graph::slot_direction(possible_connection_index) * TUNNEL_MOUTH_DEPTH;
+                        // And a faint line to where it comes out, drawn from one end only.
+                        if slot < found_junction_index {
+                            editor_gizmos.line_2d(
+                                mouth,
+                                junctions.0[found_junction_index / MAX_CONNECTIONS].0
+                                    + graph::slot_direction(found_junction_index)
+                                        * TUNNEL_MOUTH_DEPTH,
+                                bevy::color::palettes::css::DARK_SLATE_GRAY,
+                            );
+                        }
+                        mouth
+                    }
+                };
+                let color = match (passage.kind, passage.allowed) {
+                    (components::PassageKind::Tunnel, components::AgentMask::ALL) => {
+                        bevy::color::palettes::css::DEEP_SKY_BLUE
+                    }
+                    (_, allowed) => allowed.color(),
+                };
+                // Draw a line! Restricted ones (and the open end of one-way ones) get an arrow, pointing the way they let agents through.
+                if passage.allowed != components::AgentMask::NONE
+                    && (passage.allowed != components::AgentMask::ALL
+                        || connections.1[found_junction_index].allowed
+                            == components::AgentMask::NONE)
+                {
+                    editor_gizmos.arrow_2d(junction_start_pos, junction_end_pos, color);
+                } else {
+                    editor_gizmos.line_2d(junction_start_pos, junction_end_pos, color);
+                }
+                // And a happy green circle.
+                editor_gizmos.circle_2d(
+                    junction_start_pos,
+                    EDITOR_JUNCTION_CONNECTION_POINT_RADIUS,
+                    bevy::color::palettes::css::GREEN,
+                );
+            } else {
+                editor_gizmos.circle_2d(
+                    junction_start_pos,
+                    EDITOR_JUNCTION_CONNECTION_POINT_RADIUS,
+                    match possible_connection.0 {
+                        Some(value) => {
+                            if value
+                                == (junction_index * MAX_CONNECTIONS + possible_connection_index)
+                            {
+                                bevy::color::palettes::css::RED
+                            } else {
+                                bevy::color::palettes::css::LAVENDER
+                            }
+                        }
+                        None => bevy::color::palettes::css::GREY,
+                    },
+                );
+            }
+        }
+        editor_gizmos.circle_2d(junction.0, EDITOR_JUNCTION_RADIUS, junction.1.color());
+    }
+}
+
+// Rendering everything that helps line things up.
+fn render_guides(
+    mut editor_gizmos: Gizmos<EditorGizmos>,
+    snapping: Res<EditorSnapping>,
+    symmetry: Res<EditorSymmetry>,
+    level_info: Res<components::LevelInfo>,
+) {
+    // The snapping grid, covering the level. An even number of cells, so the lines land on the snapped spots.
+    if snapping.grid {
+        let cells = (level_info.size / snapping.grid_size / 2.0)
+            .ceil()
+            .as_uvec2()
+            * 2;
+        editor_gizmos.grid_2d(
+            Vec2::ZERO,
+            0.0,
+            cells,
+            Vec2::splat(snapping.grid_size),
+            bevy::color::palettes::css::DARK_SLATE_GRAY,
+        );
+    }
+    // The mirror line, right across the level.
+    if let Some(axis) = symmetry.axis {
+        let (from, to) = match axis {
+            MirrorAxis::Vertical(x) => (
+                Vec2::new(x, -level_info.size.y * 0.5),
+                Vec2::new(x, level_info.size.y * 0.5),
+            ),
+            MirrorAxis::Horizontal(y) => (
+                Vec2::new(-level_info.size.x * 0.5, y),
+                Vec2::new(level_info.size.x * 0.5, y),
+            ),
+        };
+        editor_gizmos.line_2d(from, to, bevy::color::palettes::css::FUCHSIA);
+    }
+    // Whatever's being lined up with.
+    for (from, to) in &snapping.active_guides {
+        editor_gizmos.line_2d(*from, *to, bevy::color::palettes::css::AQUA);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::components;
+use crate::core::level::{
+    graph,
+    validation::{crossing_point, validate_level, LevelIssue},
+};
+use crate::core::prelude::*;
+use bevy::{color::palettes::css, prelude::*};
+
+use super::EditorGizmos;
+
+// Everything wrong with the level right now. Only worked out again when the level changes.
+#[derive(Resource, Default)]
+pub struct LevelIssues(pub Vec<LevelIssue>);
+
+// Checking the level over whenever it's been touched.
+pub(super) fn refresh_issues(
+    junctions: Res<components::Junctions>,
+    connections: Res<components::Connections>,
+    mut issues: ResMut<LevelIssues>,
+) {
+    if !junctions.is_changed() && !connections.is_changed() {
+        return;
+    }
+    issues.0 = validate_level(&junctions, &connections);
+}
+
+// Circling every problem right where it is. Red for what stops the level from playing, orange for the rest.
+pub(super) fn render_issues(
+    mut editor_gizmos: Gizmos<EditorGizmos>,
+    issues: Res<LevelIssues>,
+    junctions: Res<components::Junctions>,
+    connections: Res<components::Connections>,
+) {
+    let radius = EDITOR_JUNCTION_RADIUS + EDITOR_JUNCTION_CONNECTION_POINT_RADIUS * 3.0;
+    let position = |junction: usize| junctions.0.get(junction).map(|junction| junction.0);
+    let slot_position =
+        |slot: usize| position(slot / MAX_CONNECTIONS).map(|at| graph::slot_position(at, slot));
+
+    for issue in &issues.0 {
+        let color = if issue.is_error() {
+            css::RED
+        } else {
+            css::ORANGE
+        };
+        match issue {
+            // Where the link goes, and the slot that never linked back.
+            LevelIssue::AsymmetricLink(slot, target) => {
+                if let (Some(from), Some(to)) = (slot_position(*slot), slot_position(*target)) {
+                    editor_gizmos.line_2d(from, to, color);
+                    editor_gizmos.circle_2d(to, EDITOR_JUNCTION_CONNECTION_POINT_RADIUS, color);
+                }
+            }
+            LevelIssue::DanglingIndex(slot, _) => {
+                if let Some(at) = slot_position(*slot) {
+                    editor_gizmos.circle_2d(at, EDITOR_JUNCTION_CONNECTION_POINT_RADIUS, color);
+                }
+            }
+            LevelIssue::UnreachableJunction(junction)
+            | LevelIssue::UnreachablePellet(junction)
+            | LevelIssue::ReversingDeadEnd(junction)
+            | LevelIssue::IsolatedJunction(junction) => {
+                if let Some(at) = position(*junction) {
+                    editor_gizmos.circle_2d(at, radius, color);
+                }
+            }
+            LevelIssue::MultiplePlayerSpawns(spawns) => {
+                for at in spawns.iter().filter_map(|junction| position(*junction)) {
+                    editor_gizmos.circle_2d(at, radius, color);
+                }
+            }
+            LevelIssue::OverlappingJunctions(junction, other) => {
+                if let (Some(at), Some(other_at)) = (position(*junction), position(*other)) {
+                    editor_gizmos.circle_2d((at + other_at) * 0.5, radius, color);
+                }
+            }
+            // Just the spot where they cross.
+            LevelIssue::CrossingLinks(slot, other_slot) => {
+                let link = |slot: usize| Some((slot, connections.0.get(slot).copied()??));
+                if let Some(at) = link(*slot)
+                    .zip(link(*other_slot))
+                    .and_then(|(link, other_link)| crossing_point(&junctions, link, other_link))
+                {
+                    editor_gizmos.circle_2d(at, EDITOR_JUNCTION_CONNECTION_POINT_RADIUS, color);
+                }
+            }
+            // Nowhere in particular to point at.
+            LevelIssue::MissingGhostHouse | LevelIssue::MissingPlayerSpawn => {}
+        }
+    }
+}
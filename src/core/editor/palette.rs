@@ -0,0 +1,102 @@
+use crate::components;
+use crate::core::editor::{
+    history::{EditHistory, EditKind},
+    symmetry::EditorSymmetry,
+    HoveredJunction,
+};
+use bevy::prelude::*;
+
+// The number keys, in the order the palette uses them.
+const BRUSH_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+#[derive(Resource)]
+pub struct JunctionBrush(pub components::JunctionType); // What new and painted junctions turn into.
+
+impl Default for JunctionBrush {
+    fn default() -> Self {
+        JunctionBrush(components::JunctionType::None)
+    }
+}
+
+// Alt+1 through however many types there are, to pick a brush. The plain number keys are for switching states.
+pub(super) fn palette_input(keys: Res<ButtonInput<KeyCode>>, mut brush: ResMut<JunctionBrush>) {
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        || !keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+    {
+        return;
+    }
+    let picked = BRUSH_KEYS
+        .iter()
+        .zip(components::JunctionType::all())
+        .find(|(key, _)| keys.just_pressed(**key))
+        .map(|(_, junction_type)| junction_type);
+    if let Some(junction_type) = picked {
+        brush.0 = junction_type;
+        info!("Brush: {junction_type:?}.");
+    }
+}
+
+// Middle click a junction to paint it, or keep holding and drag over as many as you like.
+// Starting off a junction pans instead, so that doesn't paint.
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+pub(super) fn paint_input(
+    buttons: Res<ButtonInput<MouseButton>>,
+    hovered_junction: Res<HoveredJunction>,
+    brush: Res<JunctionBrush>,
+    level_info: Res<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    connections: Res<components::Connections>,
+    mut history: ResMut<EditHistory>,
+    symmetry: Res<EditorSymmetry>,
+    mut painting: Local<bool>,
+) {
+    if buttons.just_pressed(MouseButton::Middle) {
+        *painting = hovered_junction.0.is_some();
+    }
+    if !buttons.pressed(MouseButton::Middle) {
+        if std::mem::take(&mut *painting) {
+            history.settle(); // The whole stroke comes undone in one go.
+        }
+        return;
+    }
+    if !*painting {
+        return;
+    }
+
+    let Some(junction) = hovered_junction
+        .0
+        .filter(|junction| *junction < junctions.0.len())
+    else {
+        return;
+    };
+    // Its twin gets the same.
+    let twin = symmetry
+        .axis
+        .and_then(|axis| axis.other_twin(&junctions, junction));
+    let painted: Vec<usize> = std::iter::once(junction)
+        .chain(twin)
+        .filter(|junction| junctions.0[*junction].1 != brush.0)
+        .collect();
+    if painted.is_empty() {
+        return;
+    }
+    history.record(
+        EditKind::PaintJunctions,
+        &level_info,
+        &junctions,
+        &connections,
+    );
+    for junction in painted {
+        junctions.0[junction].1 = brush.0;
+    }
+}
@@ -0,0 +1,175 @@
+use crate::components;
+use crate::core::editor::{
+    camera::EditorView, history::EditHistory, selection::NodeSelection, HoveredJunction, LinkDrag,
+    PossibleConnection,
+};
+use crate::core::level::validation::{play_blockers, LevelIssue};
+use crate::core::level::LevelReplaced;
+use crate::core::objects::agents::PlayerSpawn;
+use crate::core::prelude::*;
+use bevy::prelude::*;
+
+// Everything needed to drop back into the editor right where we left it.
+pub struct EditorSession {
+    editor: Editor, // Which editor we were in.
+    level_info: components::LevelInfo,
+    junctions: components::Junctions,
+    connections: components::Connections,
+    selection: Vec<usize>,
+    possible_connection: Option<usize>,
+    link_drag: Option<(usize, Vec2)>,
+    history: EditHistory,
+    view: EditorView,
+    replaced: bool, // Whether the level file changed while we were off playing. Then it's the new one that counts.
+}
+
+#[derive(Resource, Default)]
+pub struct PlayTest(pub Option<EditorSession>); // The editor session a test play came from. (None if we're not test playing)
+
+// P to try the level out, starting from whichever junction's under the cursor. (Or the usual spot, if there isn't one.)
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+pub(super) fn test_play_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    level_info: Res<components::LevelInfo>,
+    junctions: Res<components::Junctions>,
+    connections: Res<components::Connections>,
+    selection: Res<NodeSelection>,
+    possible_connection: Res<PossibleConnection>,
+    link_drag: Res<LinkDrag>,
+    mut history: ResMut<EditHistory>,
+    view: Res<EditorView>,
+    hovered_junction: Res<HoveredJunction>,
+    mut player_spawn: ResMut<PlayerSpawn>,
+    mut play_test: ResMut<PlayTest>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        || !keys.just_pressed(KeyCode::KeyP)
+    {
+        return;
+    }
+    let GameState::Editor(editor) = *state.get() else {
+        return;
+    };
+
+    // No playing broken levels. Picking a start makes up for a missing (or doubled up) spawn, though.
+    let start = hovered_junction.0;
+    let errors: Vec<_> = play_blockers(&junctions, &connections, start)
+        .into_iter()
+        .filter(|issue| {
+            !(start.is_some()
+                && matches!(
+                    issue,
+                    LevelIssue::MissingPlayerSpawn | LevelIssue::MultiplePlayerSpawns(_)
+                ))
+        })
+        .collect();
+    if !errors.is_empty() {
+        for error in errors {
+            warn!("Can't test play this level: {error}");
+        }
+        return;
+    }
+
+    play_test.0 = Some(EditorSession {
+        editor,
+        level_info: level_info.clone(),
+        junctions: components::Junctions(junctions.0.clone()),
+        connections: connections.clone(),
+        selection: selection.junctions.clone(),
+        possible_connection: possible_connection.0,
+        link_drag: link_drag.0,
+        history: std::mem::take(&mut *history),
+        view: view.clone(),
+        replaced: false,
+    });
+    player_spawn.0 = start;
+    next_state.set(GameState::Playing(Playing::New));
+    info!("Test playing. Escape to head back to the editor.");
+}
+
+// Straight into the action, no waiting around.
+pub(super) fn run_test_play(
+    play_test: Res<PlayTest>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if play_test.0.is_some() {
+        next_state.set(GameState::Playing(Playing::Running));
+    }
+}
+
+// Escape (or clearing the level) to put everything back the way it was.
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+pub(super) fn end_test_play(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut play_test: ResMut<PlayTest>,
+    mut level_info: ResMut<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut selection: ResMut<NodeSelection>,
+    mut possible_connection: ResMut<PossibleConnection>,
+    mut link_drag: ResMut<LinkDrag>,
+    mut history: ResMut<EditHistory>,
+    mut view: ResMut<EditorView>,
+    mut player_spawn: ResMut<PlayerSpawn>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) && *state.get() != GameState::Playing(Playing::Complete)
+    {
+        return;
+    }
+    let Some(session) = play_test.0.take() else {
+        return; // Not a test play, nothing to go back to.
+    };
+
+    // The level file changed mid-test, so the old session doesn't apply anymore. Keeping the new level, and just the view.
+    selection.clear();
+    if session.replaced {
+        possible_connection.0 = None;
+        link_drag.0 = None;
+        history.clear();
+        info!(
+            "The level changed on disk during the test play, so that's the one being edited now."
+        );
+    } else {
+        *level_info = session.level_info;
+        *junctions = session.junctions;
+        *connections = session.connections;
+        selection.junctions = session.selection;
+        possible_connection.0 = session.possible_connection;
+        link_drag.0 = session.link_drag;
+        *history = session.history;
+    }
+    *view = session.view;
+    player_spawn.0 = None;
+    next_state.set(GameState::Editor(session.editor));
+}
+
+// Keeping an eye out for the level file changing while we're off test playing.
+pub(super) fn notice_replaced_level(
+    mut level_replaced: EventReader<LevelReplaced>,
+    mut play_test: ResMut<PlayTest>,
+) {
+    if level_replaced.read().count() > 0 {
+        if let Some(session) = &mut play_test.0 {
+            session.replaced = true;
+        }
+    }
+}
+
+// Got back to the editor some other way (hitting 1, say), so there's nothing to go back to anymore.
+// The undo history still belongs to the level though, as long as it's the same one.
+pub(super) fn abandon_test_play(
+    mut play_test: ResMut<PlayTest>,
+    mut player_spawn: ResMut<PlayerSpawn>,
+    mut history: ResMut<EditHistory>,
+) {
+    if let Some(session) = play_test.0.take() {
+        if !session.replaced {
+            *history = session.history;
+        }
+        player_spawn.0 = None;
+    }
+}
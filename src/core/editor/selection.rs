@@ -0,0 +1,74 @@
+use crate::components;
+use bevy::prelude::*;
+
+// What the left mouse button is up to in the Nodes editor.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum NodeDrag {
+    Moving(usize, Vec2), // Dragging the selection around. (Grabbed junction, Offset from the cursor)
+    Boxing(Vec2, Vec2),  // Dragging a box out over empty space. (Start, End)
+}
+
+// The junctions picked out in the Nodes editor.
+#[derive(Resource, Default)]
+pub struct NodeSelection {
+    pub junctions: Vec<usize>,  // Every selected junction.
+    pub drag: Option<NodeDrag>, // Whatever the mouse is in the middle of.
+    pub twins: Vec<usize>,      // The mirrored junctions following along with a drag.
+}
+
+impl NodeSelection {
+    pub fn contains(&self, junction: usize) -> bool {
+        self.junctions.contains(&junction)
+    }
+
+    // Shift-clicking. In if it's out, out if it's in.
+    pub fn toggle(&mut self, junction: usize) {
+        match self
+            .junctions
+            .iter()
+            .position(|selected| *selected == junction)
+        {
+            Some(index) => {
+                self.junctions.remove(index);
+            }
+            None => self.junctions.push(junction),
+        }
+    }
+
+    // Adding every junction inside a box, corners in any order.
+    pub fn add_box(&mut self, junctions: &components::Junctions, corner: Vec2, other_corner: Vec2) {
+        let (minimum, maximum) = (corner.min(other_corner), corner.max(other_corner));
+        for (index, junction) in junctions.0.iter().enumerate() {
+            if junction.0.cmpge(minimum).all()
+                && junction.0.cmple(maximum).all()
+                && !self.contains(index)
+            {
+                self.junctions.push(index);
+            }
+        }
+    }
+
+    // The indices are all stale, start over.
+    pub fn clear(&mut self) {
+        self.junctions.clear();
+        self.drag = None;
+        self.twins.clear();
+    }
+}
+
+// Undoing, importing and such can pull junctions out from under the selection.
+pub(super) fn prune_selection(
+    junctions: Res<components::Junctions>,
+    mut selection: ResMut<NodeSelection>,
+) {
+    let junction_count = junctions.0.len();
+    if selection
+        .junctions
+        .iter()
+        .any(|junction| *junction >= junction_count)
+    {
+        selection
+            .junctions
+            .retain(|junction| *junction < junction_count);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::components;
+use crate::core::prelude::*;
+use bevy::prelude::*;
+
+// Keeping junctions lined up, so corridors come out straight.
+#[derive(Resource)]
+pub struct EditorSnapping {
+    pub grid: bool,                       // Whether positions get rounded onto the grid.
+    pub grid_size: f32,                   // How far apart the grid lines are.
+    pub guides: bool,                     // Whether positions line up with nearby junctions.
+    pub active_guides: Vec<(Vec2, Vec2)>, // The guide lines currently doing something, for drawing. (From, To)
+}
+
+impl Default for EditorSnapping {
+    fn default() -> Self {
+        EditorSnapping {
+            grid: false,
+            grid_size: EDITOR_GRID_SIZE,
+            guides: true,
+            active_guides: Vec::new(),
+        }
+    }
+}
+
+impl EditorSnapping {
+    // Where a junction at this position should really go.
+    // Lining up with another junction wins over the grid, one axis at a time. Anything in `ignore` isn't lined up with.
+    pub fn snap(
+        &mut self,
+        position: Vec2,
+        junctions: &components::Junctions,
+        ignore: &[usize],
+    ) -> Vec2 {
+        let mut snapped = if self.grid {
+            (position / self.grid_size).round() * self.grid_size
+        } else {
+            position
+        };
+        self.active_guides.clear();
+
+        if self.guides {
+            let others: Vec<Vec2> = junctions
+                .0
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !ignore.contains(index))
+                .map(|(_, junction)| junction.0)
+                .collect();
+
+            // The closest junction on each axis, as long as it's close enough.
+            let closest = |axis: fn(Vec2) -> f32| {
+                others
+                    .iter()
+                    .copied()
+                    .filter(|other| (axis(*other) - axis(position)).abs() < EDITOR_GUIDE_DISTANCE)
+                    .min_by(|first, second| {
+                        (axis(*first) - axis(position))
+                            .abs()
+                            .total_cmp(&(axis(*second) - axis(position)).abs())
+                    })
+            };
+            let aligned_x = closest(|point| point.x);
+            let aligned_y = closest(|point| point.y);
+
+            if let Some(aligned) = aligned_x {
+                snapped.x = aligned.x;
+            }
+            if let Some(aligned) = aligned_y {
+                snapped.y = aligned.y;
+            }
+            // Only drawing them once everything's settled, so they actually touch.
+            for aligned in aligned_x.into_iter().chain(aligned_y) {
+                self.active_guides.push((snapped, aligned));
+            }
+        }
+
+        snapped
+    }
+}
+
+// G for the grid, H for the guides, [ and ] to resize the grid.
+pub(super) fn snapping_input(
+    buttons: Res<ButtonInput<KeyCode>>,
+    mut snapping: ResMut<EditorSnapping>,
+) {
+    if buttons.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return; // Those are someone else's shortcuts.
+    }
+
+    if buttons.just_pressed(KeyCode::KeyG) {
+        snapping.grid = !snapping.grid;
+        info!(
+            "Grid snapping {}.",
+            if snapping.grid { "on" } else { "off" }
+        );
+    }
+    if buttons.just_pressed(KeyCode::KeyH) {
+        snapping.guides = !snapping.guides;
+        info!(
+            "Alignment guides {}.",
+            if snapping.guides { "on" } else { "off" }
+        );
+    }
+    if buttons.just_pressed(KeyCode::BracketRight) {
+        snapping.grid_size *= 2.0;
+        info!("Grid size {}.", snapping.grid_size);
+    }
+    if buttons.just_pressed(KeyCode::BracketLeft) {
+        snapping.grid_size = (snapping.grid_size * 0.5).max(EDITOR_GRID_MINIMUM);
+        info!("Grid size {}.", snapping.grid_size);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::components;
+use crate::core::level::graph;
+use crate::core::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
+
+// The line a level gets mirrored across.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MirrorAxis {
+    Vertical(f32),   // Left and right mirror each other across x = this.
+    Horizontal(f32), // Top and bottom mirror each other across y = this.
+}
+
+impl MirrorAxis {
+    // The same spot on the other side.
+    pub fn mirror_point(&self, point: Vec2) -> Vec2 {
+        match self {
+            MirrorAxis::Vertical(x) => Vec2::new(2.0 * x - point.x, point.y),
+            MirrorAxis::Horizontal(y) => Vec2::new(point.x, 2.0 * y - point.y),
+        }
+    }
+
+    // The same movement on the other side.
+    pub fn mirror_vector(&self, vector: Vec2) -> Vec2 {
+        match self {
+            MirrorAxis::Vertical(_) => Vec2::new(-vector.x, vector.y),
+            MirrorAxis::Horizontal(_) => Vec2::new(vector.x, -vector.y),
+        }
+    }
+
+    // The slot pointing the mirrored way round the ring.
+    // Slot k sits at 360/MAX_CONNECTIONS*k degrees, so flipping left/right sends it to 180 minus that, and flipping top/bottom to minus that.
+    pub fn mirror_slot(&self, slot: usize) -> usize {
+        let slot = slot % MAX_CONNECTIONS;
+        match self {
+            MirrorAxis::Vertical(_) => {
+                (MAX_CONNECTIONS / 2 + MAX_CONNECTIONS - slot) % MAX_CONNECTIONS
+            }
+            MirrorAxis::Horizontal(_) => (MAX_CONNECTIONS - slot) % MAX_CONNECTIONS,
+        }
+    }
+
+    // Close enough to the axis that it's its own twin.
+    pub fn on_axis(&self, point: Vec2) -> bool {
+        self.mirror_point(point).distance(point) < EDITOR_MIRROR_TOLERANCE
+    }
+
+    // The junction sitting where this one's reflection should be. A junction on the axis is its own twin.
+    pub fn twin(&self, junctions: &components::Junctions, junction: usize) -> Option<usize> {
+        let position = junctions.0[junction].0;
+        if self.on_axis(position) {
+            return Some(junction);
+        }
+        let mirrored = self.mirror_point(position);
+        junctions
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(index, other)| {
+                *index != junction && other.0.distance(mirrored) < EDITOR_MIRROR_TOLERANCE
+            })
+            .min_by(|(_, first), (_, second)| {
+                first
+                    .0
+                    .distance(mirrored)
+                    .total_cmp(&second.0.distance(mirrored))
+            })
+            .map(|(index, _)| index)
+    }
+
+    // The twin, but only if it's actually a different junction.
+    pub fn other_twin(&self, junctions: &components::Junctions, junction: usize) -> Option<usize> {
+        self.twin(junctions, junction)
+            .filter(|twin| *twin != junction)
+    }
+}
+
+// Wiring up the reflection of a link, through the mirrored slots on the twins.
+// Does nothing if there's no twin, or the slots are taken. (Links that are their own reflection take them first.)
+pub fn mirror_link(
+    axis: MirrorAxis,
+    junctions: &components::Junctions,
+    connections: &mut components::Connections,
+    first_slot: usize,
+    second_slot: usize,
+) {
+    let mirror = |slot: usize| {
+        axis.twin(junctions, slot / MAX_CONNECTIONS)
+            .map(|twin| twin * MAX_CONNECTIONS + axis.mirror_slot(slot))
+    };
+    if let (Some(first), Some(second)) = (mirror(first_slot), mirror(second_slot)) {
+        if first != second && connections.0[first].is_none() && connections.0[second].is_none() {
+            graph::link_slots(connections, first, second);
+        }
+    }
+}
+
+// Letting the same agents through the reflection of a link, as long as the reflection's actually there.
+pub fn mirror_passages(
+    axis: MirrorAxis,
+    junctions: &components::Junctions,
+    connections: &mut components::Connections,
+    slot: usize,
+) {
+    let Some(other_slot) = connections.0[slot] else {
+        return;
+    };
+    let mirror = |slot: usize| {
+        axis.twin(junctions, slot / MAX_CONNECTIONS)
+            .map(|twin| twin * MAX_CONNECTIONS + axis.mirror_slot(slot))
+    };
+    if let (Some(first), Some(second)) = (mirror(slot), mirror(other_slot)) {
+        // A link straight across the axis is its own reflection, no need to go swapping its ends.
+        if ![slot, other_slot].contains(&first) && connections.0[first] == Some(second) {
+            connections.1[first] = connections.1[slot];
+            connections.1[second] = connections.1[other_slot];
+        }
+    }
+}
+
+// Whether edits get mirrored, and across what.
+#[derive(Resource, Default)]
+pub struct EditorSymmetry {
+    pub axis: Option<MirrorAxis>, // None for no mirroring.
+}
+
+// M to flip between off, left/right and top/bottom. Shift+M to move the axis to the cursor.
+pub(super) fn symmetry_input(
+    query_windows: Query<&Window, With<PrimaryWindow>>,
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+    buttons: Res<ButtonInput<KeyCode>>,
+    mut symmetry: ResMut<EditorSymmetry>,
+) {
+    if buttons.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        || !buttons.just_pressed(KeyCode::KeyM)
+    {
+        return;
+    }
+
+    if buttons.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        let (camera, camera_transform) = query_camera.single();
+        let Some(cursor_position) = query_windows
+            .single()
+            .cursor_position()
+            .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+            .map(|ray| ray.origin.truncate())
+        else {
+            return;
+        };
+        symmetry.axis = match symmetry.axis {
+            Some(MirrorAxis::Vertical(_)) => Some(MirrorAxis::Vertical(cursor_position.x)),
+            Some(MirrorAxis::Horizontal(_)) => Some(MirrorAxis::Horizontal(cursor_position.y)),
+            None => None, // Nothing to move.
+        };
+    } else {
+        symmetry.axis = match symmetry.axis {
+            None => Some(MirrorAxis::Vertical(0.0)),
+            Some(MirrorAxis::Vertical(_)) => Some(MirrorAxis::Horizontal(0.0)),
+            Some(MirrorAxis::Horizontal(_)) => None,
+        };
+    }
+    info!("Symmetry: {:?}.", symmetry.axis);
+}
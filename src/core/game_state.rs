@@ -1,3 +1,5 @@
+use crate::components;
+use crate::core::level::validation::can_play;
 use bevy::prelude::*;
 
 pub(super) struct GameStatePlugin;
@@ -5,7 +7,14 @@ pub(super) struct GameStatePlugin;
 impl Plugin for GameStatePlugin {
     fn build(&self, app: &mut App) {
         app.insert_state(GameState::Editor(Editor::Nodes))
-            .add_systems(FixedUpdate, transition_states); // Setting our default state.
+            .add_systems(
+                FixedUpdate,
+                // Don't jump away while someone's typing in a level name (or a par time).
+                transition_states.run_if(
+                    not(in_state(GameState::Editor(Editor::New)))
+                        .and_then(not(in_state(GameState::Editor(Editor::Metadata)))),
+                ),
+            ); // Setting our default state.
     }
 }
 
@@ -14,7 +23,14 @@ impl Plugin for GameStatePlugin {
 fn transition_states(
     buttons: Res<ButtonInput<KeyCode>>,
     mut next_state: ResMut<NextState<GameState>>,
+    junctions: Res<components::Junctions>,
+    connections: Res<components::Connections>,
 ) {
+    // Alt's for picking brushes.
+    if buttons.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+        return;
+    }
+
     // match current_state.get() {
     //     Menu(menu) => todo!(),
     //     Editor(editor) => todo!(),
@@ -24,11 +40,12 @@ fn transition_states(
         next_state.set(GameState::Editor(Editor::Nodes));
     }
 
-    if buttons.just_pressed(KeyCode::Digit2) {
+    // No playing broken levels, whichever way in.
+    if buttons.just_pressed(KeyCode::Digit2) && can_play(&junctions, &connections) {
         next_state.set(GameState::Playing(Playing::New));
     }
 
-    if buttons.just_pressed(KeyCode::Digit3) {
+    if buttons.just_pressed(KeyCode::Digit3) && can_play(&junctions, &connections) {
         next_state.set(GameState::Playing(Playing::Running));
     }
 }
@@ -44,6 +61,7 @@ pub enum GameState {
 pub enum Menu {
     Title,    // The tiiiitle screen.
     Settings, // For the settings menu.
+    Victory,  // Every level's been beaten!
 }
 
 #[derive(Reflect, Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -51,6 +69,7 @@ pub enum Editor {
     New,         // Getting a new level up and running.
     Nodes,       // For adding/removing nodes and setting their types.
     Connections, // For connecting nodes together.
+    Metadata,    // For naming the level and tweaking its rules.
     Saving,      // For saving the nodes before quitting back to the main game loop.
 }
 
@@ -77,9 +96,9 @@ pub fn in_editor(current_state: Res<State<GameState>>) -> bool {
     )
 }
 
-// pub fn in_playing(current_state: Res<State<GameState>>) -> bool {
-//     matches!(
-//         current_state.get(),
-//         crate::core::prelude::GameState::Playing(_)
-//     )
-// }
+pub fn in_playing(current_state: Res<State<GameState>>) -> bool {
+    matches!(
+        current_state.get(),
+        crate::core::prelude::GameState::Playing(_)
+    )
+}
@@ -0,0 +1,224 @@
+use crate::components;
+use crate::core::editor::{
+    history::{EditHistory, EditKind},
+    selection::NodeSelection,
+    symmetry::EditorSymmetry,
+};
+use crate::core::level::graph;
+use crate::core::objects::agents::Frightened;
+use crate::core::prelude::*;
+use bevy::{
+    input::{mouse::MouseWheel, InputSystem},
+    prelude::*,
+};
+use bevy_inspector_egui::{
+    bevy_egui::{egui, EguiContexts, EguiPlugin, EguiSet},
+    quick::WorldInspectorPlugin,
+    reflect_inspector,
+};
+
+pub(super) struct InspectorPlugin;
+
+#[derive(Resource, Default)]
+struct WorldInspector(bool); // Whether the everything-at-once inspector is up.
+
+// Where a slot leads, in terms that make sense to a person.
+#[derive(Reflect, Copy, Clone, PartialEq, Default, Debug)]
+struct LinkTarget {
+    junction: usize,
+    slot: usize,
+}
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<components::DestroyWhenNotThisState>()
+            .register_type::<components::NavAgent>()
+            .register_type::<components::Junctions>()
+            .register_type::<components::Connections>()
+            .register_type::<components::LevelInfo>()
+            .register_type::<Frightened>()
+            .register_type::<Option<LinkTarget>>()
+            .init_resource::<WorldInspector>()
+            .add_plugins(EguiPlugin)
+            .add_plugins(
+                WorldInspectorPlugin::new()
+                    .run_if(|world_inspector: Res<WorldInspector>| world_inspector.0),
+            )
+            .add_systems(
+                PreUpdate,
+                block_input_under_inspector
+                    .after(InputSystem)
+                    .after(EguiSet::ProcessInput),
+            )
+            .add_systems(Update, toggle_world_inspector)
+            .add_systems(
+                Update,
+                junction_inspector.run_if(
+                    in_state(GameState::Editor(Editor::Nodes))
+                        .or_else(in_state(GameState::Editor(Editor::Connections))),
+                ),
+            );
+    }
+}
+
+// F12 to see everything, in the editor or in the middle of a game.
+fn toggle_world_inspector(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut world_inspector: ResMut<WorldInspector>,
+) {
+    if keys.just_pressed(KeyCode::F12) {
+        world_inspector.0 = !world_inspector.0;
+    }
+}
+
+// Clicks, scrolls and typing meant for an inspector shouldn't land in the level too.
+fn block_input_under_inspector(
+    mut contexts: EguiContexts,
+    mut buttons: ResMut<ButtonInput<MouseButton>>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut scroll_events: ResMut<Events<MouseWheel>>,
+) {
+    let Some(context) = contexts.try_ctx_mut() else {
+        return; // No window, nothing to get in the way.
+    };
+    if context.wants_pointer_input() || context.is_pointer_over_area() {
+        buttons.reset_all();
+        scroll_events.clear();
+    }
+    if context.wants_keyboard_input() {
+        keys.reset_all();
+    }
+}
+
+// The last junction picked, laid out field by field. Everything in it can be edited, and undone.
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+fn junction_inspector(
+    mut contexts: EguiContexts,
+    type_registry: Res<AppTypeRegistry>,
+    selection: Res<NodeSelection>,
+    level_info: Res<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut history: ResMut<EditHistory>,
+    symmetry: Res<EditorSymmetry>,
+    mut dragging: Local<bool>, // Whether a position's being dragged about, so it all undoes in one go.
+    mut draft: Local<Option<(usize, Option<LinkTarget>)>>, // A link target still being typed in. (Slot, Target)
+) {
+    let Some(junction) = selection
+        .junctions
+        .last()
+        .copied()
+        .filter(|junction| *junction < junctions.0.len())
+    else {
+        *draft = None; // Nowhere for it to go anymore.
+        return;
+    };
+    let Some(context) = contexts.try_ctx_mut() else {
+        return;
+    };
+    let type_registry = type_registry.read();
+
+    egui::Window::new("Junction")
+        .default_pos((16.0, 96.0))
+        .resizable(false)
+        .show(context, |ui| {
+            ui.label(format!("#{junction}"));
+
+            // Where it is and what it is.
+            let mut edited = junctions.0[junction].clone();
+            if reflect_inspector::ui_for_value(&mut edited, ui, &type_registry) {
+                let current = junctions.0[junction].clone();
+                let kind = if edited.1 != current.1 {
+                    EditKind::RetypeJunction
+                } else {
+                    EditKind::MoveJunctions
+                };
+                history.record(kind, &level_info, &junctions, &connections);
+                // Its twin gets the same, the other way round. Found before anything moves, while it's still sitting opposite.
+                if let Some((axis, twin)) = symmetry
+                    .axis
+                    .and_then(|axis| Some((axis, axis.other_twin(&junctions, junction)?)))
+                {
+                    junctions.0[twin].0 += axis.mirror_vector(edited.0 - current.0);
+                    if kind == EditKind::RetypeJunction {
+                        junctions.0[twin].1 = edited.1;
+                    }
+                }
+                junctions.0[junction] = edited;
+                *dragging = true;
+            } else if *dragging && !ui.input(|input| input.pointer.any_down()) {
+                history.settle(); // Let go, the next drag's its own step.
+                *dragging = false;
+            }
+
+            // Where each slot leads. Edits only take once they're finished, not every keystroke along the way.
+            ui.separator();
+            egui::Grid::new("junction_slots").show(ui, |ui| {
+                for slot in 0..MAX_CONNECTIONS {
+                    let slot_index = junction * MAX_CONNECTIONS + slot;
+                    let target = connections.0[slot_index].map(|other_slot| LinkTarget {
+                        junction: other_slot / MAX_CONNECTIONS,
+                        slot: other_slot % MAX_CONNECTIONS,
+                    });
+
+                    ui.label(format!("Slot {slot} ({}°)", 360 / MAX_CONNECTIONS * slot));
+                    let mut edited = match *draft {
+                        Some((draft_slot, draft_target)) if draft_slot == slot_index => {
+                            draft_target
+                        }
+                        _ => target,
+                    };
+                    if reflect_inspector::ui_for_value(&mut edited, ui, &type_registry) {
+                        *draft = Some((slot_index, edited));
+                    }
+                    ui.end_row();
+                }
+            });
+
+            // Enter, or clicking off somewhere else, and it's done.
+            let Some((slot_index, edited)) = *draft else {
+                return;
+            };
+            if slot_index / MAX_CONNECTIONS != junction {
+                *draft = None; // Picked another junction halfway through. Never mind.
+                return;
+            }
+            if !ui.input(|input| input.key_pressed(egui::Key::Enter))
+                && (ui.memory(|memory| memory.focused().is_some())
+                    || ui.input(|input| input.pointer.any_down()))
+            {
+                return;
+            }
+            *draft = None;
+
+            // Anything pointing off the end of the level (or back at itself) just doesn't take.
+            let other_slot = edited
+                .map(|edited| edited.junction * MAX_CONNECTIONS + edited.slot % MAX_CONNECTIONS);
+            let nonsense = other_slot.is_some_and(|other_slot| {
+                other_slot >= connections.0.len() || other_slot == slot_index
+            });
+            if !nonsense && connections.0[slot_index] != other_slot {
+                history.record(
+                    if other_slot.is_some() {
+                        EditKind::Link
+                    } else {
+                        EditKind::Unlink
+                    },
+                    &level_info,
+                    &junctions,
+                    &connections,
+                );
+                relink_slot(&mut connections, slot_index, other_slot);
+            }
+        });
+}
+
+// Pointing a slot somewhere else. Whatever either end was linked to before gets cut loose.
+fn relink_slot(connections: &mut components::Connections, slot: usize, target: Option<usize>) {
+    for end in std::iter::once(slot).chain(target) {
+        graph::unlink_slot(connections, end);
+    }
+    if let Some(target) = target {
+        graph::link_slots(connections, slot, target);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::core::level::generator::MazeSettings;
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+// Whatever was asked for on the command line.
+#[derive(Resource, Default, Debug)]
+pub struct LaunchOptions {
+    pub level: Option<PathBuf>, // --level <path> Open this level instead of the default one.
+    pub import_ascii: Option<PathBuf>, // --import-ascii <path> Start from a tile maze.
+    pub export_svg: Option<PathBuf>, // --export-svg <path> Draw the level out and quit, no window.
+    pub svg_labels: bool,       // --svg-labels Put junction indices on that drawing.
+    pub generate: Option<u64>,  // --generate <seed> Start from a freshly generated maze.
+    pub maze_size: Option<(usize, usize)>, // --maze-size <columns>x<rows> How many junctions that maze gets.
+    pub maze_density: Option<u32>,         // --maze-density <percent> How loopy it gets.
+    pub maze_asymmetric: bool,             // --maze-asymmetric Don't bother mirroring it.
+}
+
+impl LaunchOptions {
+    // Picking the options out of the arguments. Anything we don't know gets a warning and is skipped.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let mut options = LaunchOptions::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--level" => options.level = args.next().map(PathBuf::from),
+                "--import-ascii" => options.import_ascii = args.next().map(PathBuf::from),
+                "--export-svg" => options.export_svg = args.next().map(PathBuf::from),
+                "--svg-labels" => options.svg_labels = true,
+                "--generate" => options.generate = args.next().and_then(|seed| seed.parse().ok()),
+                "--maze-size" => {
+                    options.maze_size = args.next().and_then(|size| {
+                        let (columns, rows) = size.split_once('x')?;
+                        Some((columns.parse().ok()?, rows.parse().ok()?))
+                    })
+                }
+                "--maze-density" => {
+                    options.maze_density = args.next().and_then(|density| density.parse().ok())
+                }
+                "--maze-asymmetric" => options.maze_asymmetric = true,
+                _ => eprintln!("Unknown argument: {arg}"), // No logger yet, this runs before the app.
+            }
+        }
+
+        options
+    }
+
+    // The maze that was asked for, if one was. Anything not given gets the usual default.
+    pub fn maze_settings(&self) -> Option<MazeSettings> {
+        let defaults = MazeSettings::default();
+        let (columns, rows) = self.maze_size.unwrap_or((defaults.columns, defaults.rows));
+        Some(MazeSettings {
+            seed: self.generate?,
+            columns,
+            rows,
+            density: self.maze_density.unwrap_or(defaults.density),
+            mirrored: !self.maze_asymmetric,
+        })
+    }
+}
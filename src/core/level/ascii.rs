@@ -0,0 +1,300 @@
+use crate::components::{self, JunctionType};
+use crate::core::level::graph;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+// Everything that can go wrong while reading in a tile maze.
+#[derive(Debug)]
+pub enum AsciiImportError {
+    Io(std::io::Error),              // Couldn't read the file.
+    UnknownTile(usize, usize, char), // (Row, Column, Tile) Some character we don't understand.
+    Empty,                           // There's nothing to walk on.
+}
+
+impl std::fmt::Display for AsciiImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AsciiImportError::Io(error) => write!(f, "couldn't read maze: {error}"),
+            AsciiImportError::UnknownTile(row, column, tile) => {
+                write!(f, "unknown tile '{tile}' at row {row}, column {column}")
+            }
+            AsciiImportError::Empty => write!(f, "maze has no walkable tiles"),
+        }
+    }
+}
+
+impl std::error::Error for AsciiImportError {}
+
+// What each character in the maze means.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Tile {
+    Wall,        // '#', and anything off the edge of the grid.
+    Path, // '.', '-' or ' ', somewhere to walk. Lots of old mazes leave the empty corridors blank.
+    PowerPellet, // 'o'
+    GhostHouse, // 'G', however many tiles of it there are. It all ends up as the one ghost house.
+    Player, // 'P'
+}
+
+impl Tile {
+    fn from_char(tile: char) -> Option<Self> {
+        match tile {
+            '#' => Some(Tile::Wall),
+            '.' | '-' | ' ' => Some(Tile::Path),
+            'o' => Some(Tile::PowerPellet),
+            'G' => Some(Tile::GhostHouse),
+            'P' => Some(Tile::Player),
+            _ => None,
+        }
+    }
+
+    fn walkable(&self) -> bool {
+        *self != Tile::Wall
+    }
+}
+
+// Reading a maze file and turning it into a level.
+pub fn import_ascii_file(
+    path: &Path,
+    tile_size: f32,
+) -> Result<(components::Junctions, components::Connections), AsciiImportError> {
+    import_ascii(
+        &std::fs::read_to_string(path).map_err(AsciiImportError::Io)?,
+        tile_size,
+    )
+}
+
+// Turning a tile maze into junctions and connections.
+// Junctions go anywhere that isn't a straight corridor (so corners, crossings and dead ends) and on every special tile.
+pub fn import_ascii(
+    text: &str,
+    tile_size: f32,
+) -> Result<(components::Junctions, components::Connections), AsciiImportError> {
+    // Reading the tiles in.
+    let mut tiles: Vec<Vec<Tile>> = Vec::new();
+    for (row, line) in text.lines().enumerate() {
+        let mut tile_row = Vec::new();
+        for (column, character) in line.trim_end().chars().enumerate() {
+            tile_row.push(
+                Tile::from_char(character)
+                    .ok_or(AsciiImportError::UnknownTile(row, column, character))?,
+            );
+        }
+        tiles.push(tile_row);
+    }
+
+    let rows = tiles.len();
+    let columns = tiles
+        .iter()
+        .map(|tile_row| tile_row.len())
+        .max()
+        .unwrap_or(0);
+    let tile_at = |row: isize, column: isize| -> Tile {
+        if row < 0 || column < 0 {
+            return Tile::Wall;
+        }
+        tiles
+            .get(row as usize)
+            .and_then(|tile_row| tile_row.get(column as usize))
+            .copied()
+            .unwrap_or(Tile::Wall)
+    };
+
+    // Right, up, left, down. In tile space, so up is a row less.
+    let directions: [(isize, isize); 4] = [(0, 1), (-1, 0), (0, -1), (1, 0)];
+    let tile_position = |row: f32, column: f32| {
+        Vec2::new(
+            column - (columns as f32 - 1.0) * 0.5,
+            (rows as f32 - 1.0) * 0.5 - row,
+        ) * tile_size
+    };
+
+    // The ghost house sits in the middle of all its tiles.
+    let ghost_tiles: Vec<(usize, usize)> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (row, column)))
+        .filter(|(row, column)| tile_at(*row as isize, *column as isize) == Tile::GhostHouse)
+        .collect();
+    let ghost_house_position = ghost_tiles
+        .iter()
+        .map(|(row, column)| tile_position(*row as f32, *column as f32))
+        .sum::<Vec2>()
+        / ghost_tiles.len().max(1) as f32;
+
+    // Finding where the junctions go.
+    let mut junction_at: HashMap<(isize, isize), usize> = HashMap::new(); // (Row, Column) to junction.
+    let mut junctions = components::Junctions(Vec::new());
+    let mut connections = components::Connections::default();
+    let mut ghost_house = None;
+    for row in 0..rows {
+        for column in 0..columns {
+            let tile = tile_at(row as isize, column as isize);
+            if !tile.walkable() {
+                continue;
+            }
+
+            let open: Vec<bool> = directions
+                .iter()
+                .map(|(row_step, column_step)| {
+                    tile_at(row as isize + row_step, column as isize + column_step).walkable()
+                })
+                .collect();
+            let straight =
+                (open == [true, false, true, false]) || (open == [false, true, false, true]);
+
+            // The ghost house can take up lots of tiles, but we only want the ghosts spawning once.
+            // Every one of its tiles leads to the same junction, and there's no pellets in there.
+            if tile == Tile::GhostHouse {
+                let ghost_house = *ghost_house.get_or_insert_with(|| {
+                    graph::add_junction(
+                        &mut junctions,
+                        &mut connections,
+                        ghost_house_position,
+                        JunctionType::GhostHouse,
+                    )
+                });
+                junction_at.insert((row as isize, column as isize), ghost_house);
+                continue;
+            }
+
+            let junction_type = match tile {
+                Tile::PowerPellet => JunctionType::PowerPellet,
+                Tile::Player => JunctionType::BonusItem,
+                _ => JunctionType::None,
+            };
+
+            if straight && junction_type == JunctionType::None {
+                continue; // Just a corridor. Nothing to see here.
+            }
+
+            junction_at.insert(
+                (row as isize, column as isize),
+                graph::add_junction(
+                    &mut junctions,
+                    &mut connections,
+                    tile_position(row as f32, column as f32),
+                    junction_type,
+                ),
+            );
+        }
+    }
+
+    if junctions.0.is_empty() {
+        return Err(AsciiImportError::Empty);
+    }
+
+    // Walking right and down from each junction until we bump into the next one.
+    for (&(row, column), &junction) in &junction_at {
+        for (row_step, column_step) in [(0, 1), (1, 0)] {
+            let (mut walk_row, mut walk_column) = (row, column);
+            loop {
+                walk_row += row_step;
+                walk_column += column_step;
+                if !tile_at(walk_row, walk_column).walkable() {
+                    break; // Hit a wall.
+                }
+                if let Some(&other) = junction_at.get(&(walk_row, walk_column)) {
+                    // Still inside the ghost house doesn't count, and it only needs the one way out to each neighbor.
+                    if other != junction && !graph::are_linked(&connections, junction, other) {
+                        graph::link_junctions(&junctions, &mut connections, junction, other);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok((junctions, connections))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::level::validation::validate_level;
+    use crate::core::prelude::*;
+
+    // A little box with a pellet in every corner, and a chunky ghost house in the middle.
+    const MAZE: &str = "\
+#########
+#o.....o#
+#.##.##.#
+#.#GGG#.#
+#.##.##.#
+#o..P..o#
+#########";
+
+    fn links(connections: &components::Connections) -> usize {
+        connections.0.iter().flatten().count() / 2
+    }
+
+    fn count(junctions: &components::Junctions, junction_type: JunctionType) -> usize {
+        junctions
+            .0
+            .iter()
+            .filter(|junction| junction.1 == junction_type)
+            .count()
+    }
+
+    #[test]
+    fn imports_a_maze() {
+        let (junctions, connections) = import_ascii(MAZE, ASCII_TILE_SIZE).unwrap();
+
+        // Four corners, the spawn, the ghost house, and the crossing above it.
+        assert_eq!(junctions.0.len(), 7);
+        assert_eq!(count(&junctions, JunctionType::PowerPellet), 4);
+        assert_eq!(count(&junctions, JunctionType::GhostHouse), 1);
+        assert_eq!(count(&junctions, JunctionType::BonusItem), 1);
+        assert_eq!(count(&junctions, JunctionType::None), 1);
+
+        // Around the outside, and up and down through the ghost house.
+        assert_eq!(links(&connections), 8);
+        let ghost_house = junctions
+            .0
+            .iter()
+            .position(|junction| junction.1 == JunctionType::GhostHouse)
+            .unwrap();
+        let spawn = junctions
+            .0
+            .iter()
+            .position(|junction| junction.1 == JunctionType::BonusItem)
+            .unwrap();
+        assert!(graph::are_linked(&connections, ghost_house, spawn));
+
+        assert_eq!(validate_level(&junctions, &connections), Vec::new());
+    }
+
+    #[test]
+    fn ghost_house_tiles_collapse_into_one() {
+        let (junctions, _) = import_ascii(MAZE, ASCII_TILE_SIZE).unwrap();
+        let ghost_house = junctions
+            .0
+            .iter()
+            .find(|junction| junction.1 == JunctionType::GhostHouse)
+            .unwrap();
+        assert_eq!(ghost_house.0, Vec2::ZERO); // Dead center, same as the middle of its tiles.
+    }
+
+    #[test]
+    fn spaces_are_corridors() {
+        let (junctions, connections) = import_ascii(MAZE, ASCII_TILE_SIZE).unwrap();
+        let (blank_junctions, blank_connections) =
+            import_ascii(&MAZE.replace('.', " "), ASCII_TILE_SIZE).unwrap();
+        assert_eq!(junctions.0, blank_junctions.0);
+        assert_eq!(connections.0, blank_connections.0);
+    }
+
+    #[test]
+    fn unknown_tiles() {
+        assert!(matches!(
+            import_ascii("#.x#", ASCII_TILE_SIZE),
+            Err(AsciiImportError::UnknownTile(0, 2, 'x'))
+        ));
+    }
+
+    #[test]
+    fn nothing_to_walk_on() {
+        assert!(matches!(
+            import_ascii("###\n###", ASCII_TILE_SIZE),
+            Err(AsciiImportError::Empty)
+        ));
+    }
+}
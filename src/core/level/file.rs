@@ -0,0 +1,271 @@
+use crate::components;
+use crate::core::level::migrations;
+use crate::core::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// The version of the level file we currently write out.
+// Bump this (and add a step to migrations.rs) whenever the layout below changes.
+pub const LEVEL_FORMAT_VERSION: u32 = 3;
+
+// One end of a link: (Junction, Slot).
+pub type LinkEnd = (usize, usize);
+
+// What actually sits on disk. The level resources with a bit of bookkeeping.
+// Links are stored as junction and slot pairs rather than flat indices, so they read nicely and survive MAX_CONNECTIONS changing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LevelFile {
+    pub version: u32, // Which version of the format this file was written with.
+    pub max_connections: usize, // How many connection slots each junction had when saved.
+    #[serde(default)]
+    pub info: components::LevelInfo, // The name and such.
+    pub junctions: Vec<components::Junction>, // Every junction in the level.
+    pub links: Vec<(LinkEnd, LinkEnd)>, // Every connection, listed once.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub passages: Vec<(LinkEnd, components::Passage)>, // Any linked slots that aren't open to everyone.
+}
+
+// Just enough of a level file to know how to read the rest of it.
+#[derive(Deserialize)]
+pub struct VersionProbe {
+    pub version: u32,
+}
+
+// Everything that can go wrong while reading or writing a level.
+#[derive(Debug)]
+pub enum LevelFileError {
+    Io(std::io::Error),                    // Couldn't touch the file at all.
+    Serialize(ron::Error),                 // Couldn't turn the level into text.
+    Deserialize(ron::error::SpannedError), // Couldn't turn the text back into a level.
+    UnsupportedVersion(u32),               // The file is from a version we don't know about.
+    BadConnectionCount(usize, usize),      // (Expected, found) connection slots.
+    DanglingLink(LinkEnd), // A link points at a junction or slot that doesn't exist.
+    SlotTaken(LinkEnd),    // Two links are trying to use the same slot.
+}
+
+impl std::fmt::Display for LevelFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelFileError::Io(error) => write!(f, "couldn't access level file: {error}"),
+            LevelFileError::Serialize(error) => write!(f, "couldn't write level: {error}"),
+            LevelFileError::Deserialize(error) => write!(f, "couldn't read level: {error}"),
+            LevelFileError::UnsupportedVersion(version) => {
+                write!(f, "level format version {version} isn't supported")
+            }
+            LevelFileError::BadConnectionCount(expected, found) => write!(
+                f,
+                "level should have {expected} connection slots, found {found}"
+            ),
+            LevelFileError::DanglingLink((junction, slot)) => {
+                write!(
+                    f,
+                    "link to junction {junction} slot {slot}, which doesn't exist"
+                )
+            }
+            LevelFileError::SlotTaken((junction, slot)) => {
+                write!(f, "junction {junction} slot {slot} has more than one link")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelFileError {}
+
+impl From<std::io::Error> for LevelFileError {
+    fn from(error: std::io::Error) -> Self {
+        LevelFileError::Io(error)
+    }
+}
+
+impl LevelFile {
+    // Packing up the level resources.
+    pub fn from_level(
+        info: &components::LevelInfo,
+        junctions: &components::Junctions,
+        connections: &components::Connections,
+    ) -> Self {
+        LevelFile {
+            version: LEVEL_FORMAT_VERSION,
+            max_connections: MAX_CONNECTIONS,
+            info: info.clone(),
+            junctions: junctions.0.clone(),
+            links: connections
+                .0
+                .iter()
+                .enumerate()
+                .filter_map(|(slot, connection)| {
+                    connection
+                        .filter(|other_slot| slot < *other_slot) // Each link only the once.
+                        .map(|other_slot| {
+                            (
+                                (slot / MAX_CONNECTIONS, slot % MAX_CONNECTIONS),
+                                (other_slot / MAX_CONNECTIONS, other_slot % MAX_CONNECTIONS),
+                            )
+                        })
+                })
+                .collect(),
+            passages: connections
+                .1
+                .iter()
+                .enumerate()
+                .filter(|(slot, passage)| {
+                    connections.0[*slot].is_some() && **passage != components::Passage::default()
+                })
+                .map(|(slot, passage)| ((slot / MAX_CONNECTIONS, slot % MAX_CONNECTIONS), *passage))
+                .collect(),
+        }
+    }
+
+    // And unpacking them again. Only call this on a file that's been through check().
+    pub fn into_level(
+        self,
+    ) -> (
+        components::LevelInfo,
+        components::Junctions,
+        components::Connections,
+    ) {
+        let mut connections = vec![None; self.junctions.len() * MAX_CONNECTIONS];
+        for ((first_junction, first_slot), (second_junction, second_slot)) in self.links {
+            let first = first_junction * MAX_CONNECTIONS + first_slot;
+            let second = second_junction * MAX_CONNECTIONS + second_slot;
+            connections[first] = Some(second);
+            connections[second] = Some(first);
+        }
+        let mut passages = vec![components::Passage::default(); connections.len()];
+        for ((junction, slot), passage) in self.passages {
+            passages[junction * MAX_CONNECTIONS + slot] = passage;
+        }
+
+        (
+            self.info,
+            components::Junctions(self.junctions),
+            components::Connections(connections, passages),
+        )
+    }
+
+    // Turning a level into text.
+    pub fn to_ron(&self) -> Result<String, LevelFileError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(LevelFileError::Serialize)
+    }
+
+    // Turning text into a level, upgrading it from older versions and making sure it'll actually fit in the game.
+    pub fn from_ron(text: &str) -> Result<Self, LevelFileError> {
+        let probe: VersionProbe = ron::from_str(text).map_err(LevelFileError::Deserialize)?;
+
+        let level = migrations::migrate(probe.version, text)?;
+        let level = if level.max_connections != MAX_CONNECTIONS {
+            migrations::repack_slots(level)
+        } else {
+            level
+        };
+
+        level.check()?;
+        Ok(level)
+    }
+
+    // Making sure every link points somewhere real, and no slot is used twice.
+    pub fn check(&self) -> Result<(), LevelFileError> {
+        let mut used = vec![false; self.junctions.len() * self.max_connections];
+        for (first, second) in &self.links {
+            for (junction, slot) in [*first, *second] {
+                if junction >= self.junctions.len() || slot >= self.max_connections {
+                    return Err(LevelFileError::DanglingLink((junction, slot)));
+                }
+                if std::mem::replace(&mut used[junction * self.max_connections + slot], true) {
+                    return Err(LevelFileError::SlotTaken((junction, slot)));
+                }
+            }
+        }
+        for ((junction, slot), _) in &self.passages {
+            if *junction >= self.junctions.len() || *slot >= self.max_connections {
+                return Err(LevelFileError::DanglingLink((*junction, *slot)));
+            }
+        }
+        Ok(())
+    }
+
+    // Writing it out to disk, making the folder if it isn't there yet.
+    pub fn save(&self, path: &Path) -> Result<(), LevelFileError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    // Reading it back in.
+    pub fn load(path: &Path) -> Result<Self, LevelFileError> {
+        LevelFile::from_ron(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::level::templates::LevelTemplate;
+    use bevy::prelude::*;
+
+    // Saving a level to text and reading it straight back should get the exact same level.
+    fn round_trip(
+        junctions: &components::Junctions,
+        connections: &components::Connections,
+    ) -> (components::Junctions, components::Connections) {
+        let info = components::LevelInfo {
+            name: "Round Trip".to_string(),
+            ..default()
+        };
+        let text = LevelFile::from_level(&info, junctions, connections)
+            .to_ron()
+            .unwrap();
+        let (read_info, read_junctions, read_connections) =
+            LevelFile::from_ron(&text).unwrap().into_level();
+        assert_eq!(read_info, info);
+        (read_junctions, read_connections)
+    }
+
+    #[test]
+    fn empty_level_round_trips() {
+        let (junctions, connections) = LevelTemplate::Empty.build(DEFAULT_LEVEL_SIZE);
+        let (read_junctions, read_connections) = round_trip(&junctions, &connections);
+        assert!(read_junctions.0.is_empty());
+        assert_eq!(read_connections.0, connections.0);
+        assert_eq!(read_connections.1, connections.1);
+    }
+
+    #[test]
+    fn templates_round_trip() {
+        for template in [
+            LevelTemplate::Classic,
+            LevelTemplate::SquareLoop,
+            LevelTemplate::Grid(5, 4),
+        ] {
+            let (junctions, connections) = template.build(DEFAULT_LEVEL_SIZE);
+            let (read_junctions, read_connections) = round_trip(&junctions, &connections);
+            assert_eq!(read_junctions.0, junctions.0, "{template:?}");
+            assert_eq!(read_connections.0, connections.0, "{template:?}");
+            assert_eq!(read_connections.1, connections.1, "{template:?}");
+        }
+    }
+
+    #[test]
+    fn passages_round_trip() {
+        let (junctions, mut connections) = LevelTemplate::Grid(3, 3).build(DEFAULT_LEVEL_SIZE);
+        let linked: Vec<usize> = (0..connections.0.len())
+            .filter(|slot| connections.0[*slot].is_some_and(|other_slot| *slot < other_slot))
+            .collect();
+
+        // One-way, restricted to ghosts, and a tunnel, each on a different link.
+        connections.1[linked[0]].allowed = components::AgentMask::NONE;
+        connections.1[linked[1]].allowed = components::AgentMask::GHOSTS;
+        connections.1[connections.0[linked[1]].unwrap()].allowed = components::AgentMask::PLAYER;
+        for slot in [linked[2], connections.0[linked[2]].unwrap()] {
+            connections.1[slot].kind = components::PassageKind::Tunnel;
+        }
+
+        let (read_junctions, read_connections) = round_trip(&junctions, &connections);
+        assert_eq!(read_junctions.0, junctions.0);
+        assert_eq!(read_connections.0, connections.0);
+        assert_eq!(read_connections.1, connections.1);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::components::{self, JunctionType};
+use crate::core::level::graph;
+use crate::core::prelude::*;
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+// Everything that goes into a generated maze. The same settings always make the same maze.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MazeSettings {
+    pub seed: u64,
+    pub columns: usize, // Junctions across. Bumped up to odd when mirrored, so there's a middle column.
+    pub rows: usize,    // Junctions up and down.
+    pub density: u32,   // Percent chance of each spare corridor being dug out, for more loops.
+    pub mirrored: bool, // Whether the left and right halves match.
+}
+
+impl Default for MazeSettings {
+    fn default() -> Self {
+        MazeSettings {
+            seed: 0,
+            columns: DEFAULT_MAZE_SIZE.0,
+            rows: DEFAULT_MAZE_SIZE.1,
+            density: DEFAULT_MAZE_DENSITY,
+            mirrored: true,
+        }
+    }
+}
+
+// A random maze on a grid, stretched to fit the given size and centered on the origin.
+// Everything's reachable, nothing's a dead end, and nothing crosses.
+pub fn build_maze(
+    junctions: &mut components::Junctions,
+    connections: &mut components::Connections,
+    size: Vec2,
+    settings: &MazeSettings,
+) {
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    let rows = settings.rows.max(3);
+    let columns = if settings.mirrored {
+        settings.columns.max(3) | 1
+    } else {
+        settings.columns.max(3)
+    };
+    let cell = |column: usize, row: usize| row * columns + column;
+
+    // Ghosts in the middle, the player a little below, power pellets in the corners.
+    let house = cell(columns / 2, rows / 2);
+    let spawn = cell(columns / 2, (rows / 2).saturating_sub(2));
+    let corners = [
+        cell(0, 0),
+        cell(columns - 1, 0),
+        cell(0, rows - 1),
+        cell(columns - 1, rows - 1),
+    ];
+    let spacing = size / Vec2::new(columns as f32 - 1.0, rows as f32 - 1.0);
+    for row in 0..rows {
+        for column in 0..columns {
+            let index = cell(column, row);
+            let junction_type = if index == house {
+                JunctionType::GhostHouse
+            } else if index == spawn {
+                JunctionType::BonusItem
+            } else if corners.contains(&index) {
+                JunctionType::PowerPellet
+            } else {
+                JunctionType::None
+            };
+            graph::add_junction(
+                junctions,
+                connections,
+                Vec2::new(column as f32, row as f32) * spacing - size * 0.5,
+                junction_type,
+            );
+        }
+    }
+
+    // Every corridor that could be dug, between grid neighbors. Mirrored corridors get dug together.
+    let mirror = |junction: usize| cell(columns - 1 - junction % columns, junction / columns);
+    let mut corridors: Vec<Vec<(usize, usize)>> = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let here = cell(column, row);
+            let neighbors = [
+                (column + 1 < columns).then(|| cell(column + 1, row)),
+                (row + 1 < rows).then(|| cell(column, row + 1)),
+            ];
+            for there in neighbors.into_iter().flatten() {
+                let corridor = (here, there);
+                let reflection = (
+                    mirror(here).min(mirror(there)),
+                    mirror(here).max(mirror(there)),
+                );
+                if !settings.mirrored || reflection == corridor {
+                    corridors.push(vec![corridor]);
+                } else if corridor < reflection {
+                    corridors.push(vec![corridor, reflection]);
+                }
+            }
+        }
+    }
+    corridors.shuffle(&mut rng);
+
+    // Digging out a spanning tree so everything's reachable, with the odd spare corridor thrown in for loops.
+    let mut groups: Vec<usize> = (0..rows * columns).collect();
+    let mut dug = vec![false; corridors.len()];
+    for (index, corridor) in corridors.iter().enumerate() {
+        let joins_up = corridor
+            .iter()
+            .any(|(here, there)| find_group(&mut groups, *here) != find_group(&mut groups, *there));
+        if joins_up || rng.gen_ratio(settings.density.min(100), 100) {
+            for (here, there) in corridor {
+                let group = find_group(&mut groups, *here);
+                let other_group = find_group(&mut groups, *there);
+                groups[group] = other_group;
+            }
+            dug[index] = true;
+        }
+    }
+
+    // Knocking through the end of every dead end. Every junction has at least two neighbors to pick from.
+    for junction in 0..rows * columns {
+        let touching: Vec<usize> = (0..corridors.len())
+            .filter(|index| {
+                corridors[*index]
+                    .iter()
+                    .any(|(here, there)| *here == junction || *there == junction)
+            })
+            .collect();
+        let dug_out = touching
+            .iter()
+            .filter(|index| dug[**index])
+            .map(|index| {
+                corridors[*index]
+                    .iter()
+                    .filter(|(here, there)| *here == junction || *there == junction)
+                    .count()
+            })
+            .sum::<usize>();
+        if dug_out < 2 {
+            let undug: Vec<usize> = touching.into_iter().filter(|index| !dug[*index]).collect();
+            if let Some(index) = undug.choose(&mut rng) {
+                dug[*index] = true;
+            }
+        }
+    }
+
+    // And actually wiring it all up.
+    for corridor in corridors
+        .iter()
+        .zip(&dug)
+        .filter(|(_, dug)| **dug)
+        .map(|(corridor, _)| corridor)
+    {
+        for (here, there) in corridor {
+            graph::link_junctions(junctions, connections, *here, *there);
+        }
+    }
+}
+
+// Which group a junction's been dug into, squashing the path down as it goes.
+fn find_group(groups: &mut [usize], mut junction: usize) -> usize {
+    while groups[junction] != junction {
+        groups[junction] = groups[groups[junction]];
+        junction = groups[junction];
+    }
+    junction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::level::validation::{validate_level, LevelIssue};
+
+    fn build(settings: MazeSettings) -> (components::Junctions, components::Connections) {
+        let mut junctions = components::Junctions(Vec::new());
+        let mut connections = components::Connections::default();
+        build_maze(
+            &mut junctions,
+            &mut connections,
+            DEFAULT_LEVEL_SIZE,
+            &settings,
+        );
+        (junctions, connections)
+    }
+
+    #[test]
+    fn same_seed_same_maze() {
+        let settings = MazeSettings {
+            seed: 42,
+            ..default()
+        };
+        let (junctions, connections) = build(settings);
+        let (other_junctions, other_connections) = build(settings);
+        assert_eq!(junctions.0, other_junctions.0);
+        assert_eq!(connections.0, other_connections.0);
+        assert_eq!(connections.1, other_connections.1);
+    }
+
+    #[test]
+    fn different_seed_different_maze() {
+        let (_, connections) = build(MazeSettings {
+            seed: 1,
+            ..default()
+        });
+        let (_, other_connections) = build(MazeSettings {
+            seed: 2,
+            ..default()
+        });
+        assert_ne!(connections.0, other_connections.0);
+    }
+
+    #[test]
+    fn every_maze_is_playable() {
+        for (columns, rows) in [(1, 1), (3, 3), (4, 3), (5, 6), (9, 7), (14, 11)] {
+            for density in [0, DEFAULT_MAZE_DENSITY, 100] {
+                for mirrored in [true, false] {
+                    for seed in 0..8 {
+                        let settings = MazeSettings {
+                            seed,
+                            columns,
+                            rows,
+                            density,
+                            mirrored,
+                        };
+                        let (junctions, connections) = build(settings);
+                        let issues: Vec<LevelIssue> = validate_level(&junctions, &connections)
+                            .into_iter()
+                            .filter(|issue| {
+                                issue.is_error() || matches!(issue, LevelIssue::ReversingDeadEnd(_))
+                            })
+                            .collect();
+                        assert_eq!(issues, Vec::new(), "{settings:?}");
+                    }
+                }
+            }
+        }
+    }
+}
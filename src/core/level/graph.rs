@@ -0,0 +1,478 @@
+use crate::components;
+use crate::core::prelude::*;
+use bevy::math::DVec2;
+use bevy::prelude::*;
+
+// The angle (in radians) a connection slot points towards. Slot 0 faces right, and they go counter-clockwise.
+pub fn slot_angle(slot: usize) -> f32 {
+    (360 / MAX_CONNECTIONS * (slot % MAX_CONNECTIONS)) as f32 * std::f32::consts::PI / 180.0
+}
+
+// Which way a slot points, as a unit vector.
+pub fn slot_direction(slot: usize) -> Vec2 {
+    Vec2::from_angle(slot_angle(slot))
+}
+
+// Where a slot sits on the edge of a junction, same as the editor draws it.
+pub fn slot_position(junction_position: Vec2, slot: usize) -> Vec2 {
+    Quat::mul_vec3(
+        Quat::from_rotation_z(slot_angle(slot)),
+        Vec3::new(EDITOR_JUNCTION_RADIUS, 0.0_f32, 0.0_f32),
+    )
+    .xy()
+        + junction_position
+}
+
+// The slot that best points along a direction.
+pub fn slot_towards(direction: Vec2) -> usize {
+    let angle = direction
+        .y
+        .atan2(direction.x)
+        .rem_euclid(std::f32::consts::TAU);
+    (angle / std::f32::consts::TAU * MAX_CONNECTIONS as f32).round() as usize % MAX_CONNECTIONS
+}
+
+// The slot directly across the ring from this one.
+pub fn opposite_slot(slot: usize) -> usize {
+    (slot + MAX_CONNECTIONS / 2) % MAX_CONNECTIONS
+}
+
+// Tacking a fresh junction (and its empty slots) onto the end of the level.
+pub fn add_junction(
+    junctions: &mut components::Junctions,
+    connections: &mut components::Connections,
+    position: Vec2,
+    junction_type: components::JunctionType,
+) -> usize {
+    junctions
+        .0
+        .push(components::Junction(position, junction_type));
+    for _i in 0..MAX_CONNECTIONS {
+        connections.0.push(None); // Creating new spaces.
+        connections.1.push(components::Passage::default());
+    }
+    junctions.0.len() - 1
+}
+
+// Wiring two slots together, both ways. Fresh links are open to everyone.
+pub fn link_slots(connections: &mut components::Connections, first: usize, second: usize) {
+    connections.0[first] = Some(second);
+    connections.0[second] = Some(first);
+    connections.1[first] = components::Passage::default();
+    connections.1[second] = components::Passage::default();
+}
+
+// Cutting a slot loose from whatever it's linked to, both ways. Empty slots go back to being open to everyone.
+pub fn unlink_slot(connections: &mut components::Connections, slot: usize) {
+    if let Some(other_slot) = connections.0[slot].take() {
+        if let Some(other_end) = connections.0.get_mut(other_slot) {
+            *other_end = None;
+            connections.1[other_slot] = components::Passage::default();
+        }
+    }
+    connections.1[slot] = components::Passage::default();
+}
+
+// Wiring two junctions together through whichever slots face each other.
+// Returns the slots that got linked, or None if either of them is already taken.
+pub fn link_junctions(
+    junctions: &components::Junctions,
+    connections: &mut components::Connections,
+    first: usize,
+    second: usize,
+) -> Option<(usize, usize)> {
+    let slot = slot_towards(junctions.0[second].0 - junctions.0[first].0);
+    let first_slot = first * MAX_CONNECTIONS + slot;
+    let second_slot = second * MAX_CONNECTIONS + opposite_slot(slot);
+
+    if first == second
+        || connections.0[first_slot].is_some()
+        || connections.0[second_slot].is_some()
+    {
+        return None;
+    }
+
+    link_slots(connections, first_slot, second_slot);
+    Some((first_slot, second_slot))
+}
+
+// Pulling a junction out of the level, along with every link to it.
+// Everything after it shuffles down an index.
+pub fn remove_junction(
+    junctions: &mut components::Junctions,
+    connections: &mut components::Connections,
+    junction: usize,
+) {
+    // Removing the connections to (and from) this junction.
+    for current_connection in 0..MAX_CONNECTIONS {
+        unlink_slot(connections, junction * MAX_CONNECTIONS + current_connection);
+    }
+
+    // And just toss these connections, after cleaning data.
+    connections
+        .0
+        .drain(junction * MAX_CONNECTIONS..(junction + 1) * MAX_CONNECTIONS);
+    connections
+        .1
+        .drain(junction * MAX_CONNECTIONS..(junction + 1) * MAX_CONNECTIONS);
+
+    // And shuffle everything on the right back a bit.
+    for connection in connections.0.iter_mut().flatten() {
+        if *connection > junction * MAX_CONNECTIONS {
+            *connection -= MAX_CONNECTIONS; // Decrementing the found value.
+        }
+    }
+
+    // Delebing it, for real this time.
+    junctions.0.remove(junction);
+}
+
+// Whether two junctions already have a link between them, through any slots.
+pub fn are_linked(connections: &components::Connections, first: usize, second: usize) -> bool {
+    connections.0[first * MAX_CONNECTIONS..(first + 1) * MAX_CONNECTIONS]
+        .iter()
+        .flatten()
+        .any(|other_slot| other_slot / MAX_CONNECTIONS == second)
+}
+
+// Reaching out from each of these junctions along every free slot, and linking to the nearest junction that way that's got the facing slot free.
+// Only the closest junction in each slot's direction counts, and only if it's close enough and lined up well enough. Anything behind it is blocked.
+// Anything can be linked to, but only these junctions go looking. Returns every link that got made. (First slot, Second slot)
+pub fn auto_connect(
+    junctions: &components::Junctions,
+    connections: &mut components::Connections,
+    from: &[usize],
+) -> Vec<(usize, usize)> {
+    // Every pairing worth considering, closest first, so each slot ends up with the nearest one it can have.
+    let mut candidates = Vec::new();
+    for junction in from {
+        // The nearest junction each slot points towards.
+        let mut nearest: [Option<(f32, usize)>; MAX_CONNECTIONS] = [None; MAX_CONNECTIONS];
+        for (other, other_junction) in junctions.0.iter().enumerate() {
+            let direction = other_junction.0 - junctions.0[*junction].0;
+            if other == *junction || direction == Vec2::ZERO {
+                continue;
+            }
+            let slot = slot_towards(direction);
+            if nearest[slot].is_none_or(|(distance, _)| direction.length() < distance) {
+                nearest[slot] = Some((direction.length(), other));
+            }
+        }
+
+        for (slot, (distance, other)) in nearest
+            .into_iter()
+            .enumerate()
+            .filter_map(|(slot, nearest)| Some((slot, nearest?)))
+        {
+            let direction = junctions.0[other].0 - junctions.0[*junction].0;
+            if distance <= EDITOR_AUTO_CONNECT_RANGE
+                && direction.angle_between(slot_direction(slot)).abs()
+                    <= EDITOR_AUTO_CONNECT_TOLERANCE.to_radians()
+            {
+                candidates.push((distance, *junction, other));
+            }
+        }
+    }
+    candidates.sort_by(|first, second| first.0.total_cmp(&second.0));
+
+    let mut made = Vec::new();
+    for (_, junction, other) in candidates {
+        if are_linked(connections, junction, other) {
+            continue; // Once is plenty.
+        }
+        made.extend(link_junctions(junctions, connections, junction, other));
+    }
+    made
+}
+
+// Nudging junctions until every link runs straight out of the slot it uses. Links stay exactly as they are.
+// Only these junctions get to move, everything else stays put. Of every layout that lines the links up, this lands on the one
+// closest to where things already are (least squares, so it's the squared distances that get kept small).
+// When the slots at the two ends of a link don't face each other there's no such layout, so it gets as close as it can and says so.
+// Returns how far things moved, all told.
+pub fn straighten_links(
+    junctions: &mut components::Junctions,
+    connections: &components::Connections,
+    movable: &[usize],
+) -> f32 {
+    // Which junctions get to move, and where they sit among the unknowns.
+    let mut unknowns = vec![None; junctions.0.len()];
+    let mut count = 0;
+    for junction in movable {
+        if let Some(unknown @ None) = unknowns.get_mut(*junction) {
+            *unknown = Some(count);
+            count += 1;
+        }
+    }
+    if count == 0 {
+        return 0.0;
+    }
+
+    // Every link that has a moving end, along with the way its slot points. (Junction, Other, Direction)
+    let mut links = Vec::new();
+    for (slot, target) in connections.0.iter().enumerate() {
+        let Some(target) = *target else {
+            continue;
+        };
+        // Tunnels go off the edge of the level, they can point however they like.
+        if target >= connections.0.len()
+            || connections.1[slot].kind == components::PassageKind::Tunnel
+        {
+            continue;
+        }
+        let (junction, other) = (slot / MAX_CONNECTIONS, target / MAX_CONNECTIONS);
+        if junction == other || junction >= junctions.0.len() || other >= junctions.0.len() {
+            continue;
+        }
+        if unknowns[junction].is_some() || unknowns[other].is_some() {
+            links.push((junction, other, slot_direction(slot).as_dvec2()));
+        }
+    }
+
+    // Doing the sums in f64, f32 runs out of digits long before a big level does.
+    let original: Vec<DVec2> = junctions
+        .0
+        .iter()
+        .map(|junction| junction.0.as_dvec2())
+        .collect();
+    let position = |moves: &[DVec2], junction: usize| {
+        original[junction] + unknowns[junction].map_or(DVec2::ZERO, |unknown| moves[unknown])
+    };
+
+    // Straight means the other end is nowhere to either side of the slot's line.
+    // Links that come out folded back (or too short) get held out at the minimum length, and it all gets solved again.
+    let mut held = vec![false; links.len()];
+    let (moves, converged) = loop {
+        let mut rules = Vec::new();
+        for (&(junction, other, direction), &held) in links.iter().zip(&held) {
+            rules.push((junction, other, direction.perp(), 0.0));
+            if held {
+                rules.push((
+                    junction,
+                    other,
+                    direction,
+                    EDITOR_STRAIGHTEN_MINIMUM_LENGTH as f64,
+                ));
+            }
+        }
+        let (moves, converged) = solve_moves(&rules, &unknowns, &original, count);
+
+        let mut held_more = false;
+        for (&(junction, other, direction), held) in links.iter().zip(held.iter_mut()) {
+            let length = (position(&moves, other) - position(&moves, junction)).dot(direction);
+            if !*held
+                && length < (EDITOR_STRAIGHTEN_MINIMUM_LENGTH - EDITOR_STRAIGHTEN_TOLERANCE) as f64
+            {
+                *held = true;
+                held_more = true;
+            }
+        }
+        if !held_more {
+            break (moves, converged);
+        }
+    };
+
+    if !converged {
+        warn!(
+            "Straightening gave up after {} iterations, the layout might still be a bit off.",
+            EDITOR_STRAIGHTEN_ITERATIONS
+        );
+    }
+    let crooked = links
+        .iter()
+        .filter(|&&(junction, other, direction)| {
+            (position(&moves, other) - position(&moves, junction))
+                .dot(direction.perp())
+                .abs()
+                > EDITOR_STRAIGHTEN_TOLERANCE as f64
+        })
+        .count();
+    if crooked > 0 {
+        warn!(
+            "Couldn't straighten {} link end(s), their slots don't face each other.",
+            crooked
+        );
+    }
+
+    let mut moved = 0.0;
+    for (junction, unknown) in unknowns.iter().enumerate() {
+        if let Some(unknown) = unknown {
+            junctions.0[junction].0 = position(&moves, junction).as_vec2();
+            moved += moves[*unknown].length() as f32;
+        }
+    }
+    moved
+}
+
+// How far each moving junction has to go so that every rule comes out right, or as close to right as it can.
+// A rule says the other end, seen along the normal from the junction, sits at the target. (Junction, Other, Normal, Target)
+// It's conjugate gradients on the least squares problem (CGLS). Starting from nowhere, it finds the smallest moves that do the job.
+// Returns the moves, and whether it settled before running out of iterations.
+fn solve_moves(
+    rules: &[(usize, usize, DVec2, f64)],
+    unknowns: &[Option<usize>],
+    original: &[DVec2],
+    count: usize,
+) -> (Vec<DVec2>, bool) {
+    // What each rule makes of some moves, and which way each rule pulls the junctions.
+    let apply = |moves: &[DVec2]| -> Vec<f64> {
+        rules
+            .iter()
+            .map(|&(junction, other, normal, _)| {
+                let moved = |junction: usize| {
+                    unknowns[junction].map_or(DVec2::ZERO, |unknown| moves[unknown])
+                };
+                normal.dot(moved(other) - moved(junction))
+            })
+            .collect()
+    };
+    let pull = |misses: &[f64]| -> Vec<DVec2> {
+        let mut pulls = vec![DVec2::ZERO; count];
+        for (&(junction, other, normal, _), miss) in rules.iter().zip(misses) {
+            if let Some(unknown) = unknowns[other] {
+                pulls[unknown] += normal * *miss;
+            }
+            if let Some(unknown) = unknowns[junction] {
+                pulls[unknown] -= normal * *miss;
+            }
+        }
+        pulls
+    };
+    let dot = |first: &[DVec2], second: &[DVec2]| -> f64 {
+        first
+            .iter()
+            .zip(second)
+            .map(|(first, second)| first.dot(*second))
+            .sum()
+    };
+
+    // How far off each rule is with nothing moved yet.
+    let mut misses: Vec<f64> = rules
+        .iter()
+        .map(|&(junction, other, normal, target)| {
+            target - normal.dot(original[other] - original[junction])
+        })
+        .collect();
+    let mut moves = vec![DVec2::ZERO; count];
+    let mut gradient = pull(&misses);
+    let mut step = gradient.clone();
+    let mut gradient_length = dot(&gradient, &gradient);
+
+    for _ in 0..EDITOR_STRAIGHTEN_ITERATIONS {
+        if gradient_length.sqrt() < (EDITOR_STRAIGHTEN_TOLERANCE as f64).powi(2) {
+            return (moves, true); // Close enough, nobody's going to see the difference.
+        }
+        let change = apply(&step);
+        let change_length: f64 = change.iter().map(|change| change * change).sum();
+        if change_length == 0.0 {
+            return (moves, true);
+        }
+        let scale = gradient_length / change_length;
+        for (moved, step) in moves.iter_mut().zip(&step) {
+            *moved += *step * scale;
+        }
+        for (miss, change) in misses.iter_mut().zip(&change) {
+            *miss -= change * scale;
+        }
+        gradient = pull(&misses);
+        let next_length = dot(&gradient, &gradient);
+        for (step, gradient) in step.iter_mut().zip(&gradient) {
+            *step = *gradient + *step * (next_length / gradient_length);
+        }
+        gradient_length = next_length;
+    }
+    let converged = gradient_length.sqrt() < (EDITOR_STRAIGHTEN_TOLERANCE as f64).powi(2);
+    (moves, converged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::JunctionType;
+
+    fn level(positions: &[Vec2]) -> (components::Junctions, components::Connections) {
+        let mut junctions = components::Junctions(Vec::new());
+        let mut connections = components::Connections::default();
+        for position in positions {
+            add_junction(
+                &mut junctions,
+                &mut connections,
+                *position,
+                JunctionType::None,
+            );
+        }
+        (junctions, connections)
+    }
+
+    #[test]
+    fn auto_connect_links_neighbors() {
+        let (junctions, mut connections) =
+            level(&[Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(0.0, 100.0)]);
+        auto_connect(&junctions, &mut connections, &[0]);
+        assert!(are_linked(&connections, 0, 1));
+        assert!(are_linked(&connections, 0, 2));
+    }
+
+    #[test]
+    fn auto_connect_is_blocked_by_nearer_junctions() {
+        // Junction 2 is straight behind junction 1, and only junction 0 goes looking.
+        let (junctions, mut connections) =
+            level(&[Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(200.0, 10.0)]);
+        auto_connect(&junctions, &mut connections, &[0]);
+        assert!(are_linked(&connections, 0, 1));
+        assert!(!are_linked(&connections, 0, 2));
+    }
+
+    #[test]
+    fn auto_connect_skips_far_and_crooked_junctions() {
+        let (junctions, mut connections) = level(&[
+            Vec2::ZERO,
+            Vec2::new(EDITOR_AUTO_CONNECT_RANGE * 2.0, 0.0),
+            Vec2::new(100.0, 35.0), // About 19 degrees off the nearest slot.
+        ]);
+        assert!(auto_connect(&junctions, &mut connections, &[0]).is_empty());
+    }
+
+    #[test]
+    fn straighten_moves_as_little_as_it_can() {
+        // Both ends can move, so each goes half way.
+        let (mut junctions, mut connections) = level(&[Vec2::ZERO, Vec2::new(100.0, 10.0)]);
+        link_slots(&mut connections, 0, MAX_CONNECTIONS + opposite_slot(0));
+        straighten_links(&mut junctions, &connections, &[0, 1]);
+        assert!(junctions.0[0].0.distance(Vec2::new(0.0, 5.0)) < 0.01);
+        assert!(junctions.0[1].0.distance(Vec2::new(100.0, 5.0)) < 0.01);
+    }
+
+    #[test]
+    fn straighten_leaves_everything_else_alone() {
+        let (mut junctions, mut connections) = level(&[Vec2::ZERO, Vec2::new(100.0, 10.0)]);
+        link_slots(&mut connections, 0, MAX_CONNECTIONS + opposite_slot(0));
+        straighten_links(&mut junctions, &connections, &[1]);
+        assert_eq!(junctions.0[0].0, Vec2::ZERO);
+        assert!(junctions.0[1].0.distance(Vec2::new(100.0, 0.0)) < 0.01);
+    }
+
+    #[test]
+    fn straighten_unfolds_links() {
+        // The link leaves to the right, but the other end is off to the left.
+        let (mut junctions, mut connections) = level(&[Vec2::ZERO, Vec2::new(-100.0, 0.0)]);
+        link_slots(&mut connections, 0, MAX_CONNECTIONS + opposite_slot(0));
+        straighten_links(&mut junctions, &connections, &[1]);
+        assert!(
+            junctions.0[1]
+                .0
+                .distance(Vec2::new(EDITOR_STRAIGHTEN_MINIMUM_LENGTH, 0.0))
+                < 0.01
+        );
+    }
+
+    #[test]
+    fn straighten_settles_when_slots_disagree() {
+        // Leaving right from one end and up from the other, that can't ever be straight.
+        let (mut junctions, mut connections) = level(&[Vec2::ZERO, Vec2::new(100.0, 100.0)]);
+        link_slots(&mut connections, 0, MAX_CONNECTIONS + opposite_slot(2));
+        let moved = straighten_links(&mut junctions, &connections, &[0, 1]);
+        assert!(moved.is_finite());
+        assert!(junctions.0.iter().all(|junction| junction.0.is_finite()));
+    }
+}
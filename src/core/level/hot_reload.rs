@@ -0,0 +1,130 @@
+use crate::components;
+use crate::core::level::file::{LevelFile, LevelFileError};
+use crate::core::level::{validation::can_play, LevelPath, LevelReplaced};
+use crate::core::prelude::*;
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use std::path::Path;
+
+// A level file, as far as the asset server's concerned.
+#[derive(Asset, TypePath)]
+pub struct LevelAsset(pub LevelFile);
+
+// Reading level files for the asset server, so it can tell us when they change.
+#[derive(Default)]
+pub struct LevelAssetLoader;
+
+impl AssetLoader for LevelAssetLoader {
+    type Asset = LevelAsset;
+    type Settings = ();
+    type Error = LevelFileError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).await?;
+        Ok(LevelAsset(LevelFile::from_ron(&text)?))
+    }
+
+    // Packs (and anything else) are .ron too, so levels get their own.
+    fn extensions(&self) -> &[&str] {
+        &[LEVEL_FILE_EXTENSION]
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct WatchedLevel(pub Option<Handle<LevelAsset>>); // The level file we're keeping an eye on.
+
+// The asset server only sees inside the assets folder, so the path has to be relative to that.
+fn asset_path(level_path: &Path) -> Option<&Path> {
+    level_path.strip_prefix("assets").ok()
+}
+
+// Keeping an eye on whichever file the level's coming from, swapping over whenever that changes.
+pub(super) fn watch_level(
+    asset_server: Res<AssetServer>,
+    level_path: Res<LevelPath>,
+    mut watched_level: ResMut<WatchedLevel>,
+) {
+    // Already watching the right thing.
+    if !level_path.is_changed() && watched_level.0.is_some() {
+        return;
+    }
+
+    // Nothing to watch until something's been saved there.
+    if !level_path.0.exists() {
+        watched_level.0 = None;
+        return;
+    }
+
+    let is_level_file = level_path.0.file_name().is_some_and(|name| {
+        name.to_string_lossy()
+            .ends_with(&format!(".{LEVEL_FILE_EXTENSION}"))
+    });
+    watched_level.0 = match asset_path(&level_path.0).filter(|_| is_level_file) {
+        Some(asset_path) => Some(asset_server.load(asset_path.to_path_buf())),
+        None => {
+            if level_path.is_changed() {
+                info!(
+                    "{} isn't a .{LEVEL_FILE_EXTENSION} file in the assets folder, it won't be reloaded when it changes.",
+                    level_path.0.display()
+                );
+            }
+            None
+        }
+    };
+}
+
+// Something changed the level file on disk. Out with the old level, in with the new.
+// Mid-game, a level that can't be played doesn't get swapped in. The editor takes it either way, that's where it gets fixed.
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+pub fn reload_level(
+    mut asset_events: EventReader<AssetEvent<LevelAsset>>,
+    state: Res<State<GameState>>,
+    level_assets: Res<Assets<LevelAsset>>,
+    watched_level: Res<WatchedLevel>,
+    mut level_info: ResMut<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut level_replaced: EventWriter<LevelReplaced>,
+) {
+    let Some(watched_handle) = &watched_level.0 else {
+        asset_events.clear();
+        return;
+    };
+
+    for asset_event in asset_events.read() {
+        let AssetEvent::Modified { id } = asset_event else {
+            continue; // The first load's already been handled, we only care about changes.
+        };
+        if *id != watched_handle.id() {
+            continue;
+        }
+        let Some(level_asset) = level_assets.get(*id) else {
+            continue;
+        };
+
+        // Saving from the editor changes the file too. No need to reload what we've already got.
+        if LevelFile::from_level(&level_info, &junctions, &connections) == level_asset.0 {
+            continue;
+        }
+
+        let (new_level_info, new_junctions, new_connections) = level_asset.0.clone().into_level();
+        if !can_play(&new_junctions, &new_connections)
+            && matches!(state.get(), GameState::Playing(_))
+        {
+            warn!(
+                "Not reloading \"{}\" mid-game until it's fixed.",
+                new_level_info.name
+            );
+            continue;
+        }
+        (*level_info, *junctions, *connections) = (new_level_info, new_junctions, new_connections);
+        level_replaced.send(LevelReplaced);
+        info!("Reloaded level \"{}\" from disk.", level_info.name);
+    }
+}
@@ -0,0 +1,476 @@
+use crate::components::{self, JunctionType};
+use crate::core::level::file::{LevelFile, LevelFileError, LinkEnd, LEVEL_FORMAT_VERSION};
+use crate::core::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// Reading a level written by any version we know about, and bringing it up to the current one.
+// Each old version gets its own frozen copy of the types it was written with, so changing the live types never breaks old files.
+pub fn migrate(version: u32, text: &str) -> Result<LevelFile, LevelFileError> {
+    match version {
+        1 => Ok(from_v2(from_v1(
+            ron::from_str(text).map_err(LevelFileError::Deserialize)?,
+        )?)),
+        2 => Ok(from_v2(
+            ron::from_str(text).map_err(LevelFileError::Deserialize)?,
+        )),
+        LEVEL_FORMAT_VERSION => ron::from_str(text).map_err(LevelFileError::Deserialize),
+        _ => Err(LevelFileError::UnsupportedVersion(version)),
+    }
+}
+
+// Version 1: the flat connection list, straight out of components::Connections.
+#[derive(Deserialize)]
+struct LevelFileV1 {
+    max_connections: usize,
+    #[serde(default)]
+    info: LevelInfoV1,
+    junctions: Vec<JunctionV1>,
+    connections: Vec<Option<usize>>,
+}
+
+#[derive(Deserialize)]
+struct LevelInfoV1 {
+    name: String,
+    size: Vec2,
+}
+
+impl Default for LevelInfoV1 {
+    fn default() -> Self {
+        LevelInfoV1 {
+            name: "Untitled".to_string(),
+            size: DEFAULT_LEVEL_SIZE,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JunctionV1(Vec2, JunctionTypeV1);
+
+#[derive(Deserialize)]
+enum JunctionTypeV1 {
+    None,
+    PowerPellet,
+    GhostHouse,
+    BonusItem,
+}
+
+// Where each version 1 junction type ended up. If a type ever goes away, this is where it gets mapped onto something else.
+impl From<JunctionTypeV1> for JunctionType {
+    fn from(junction_type: JunctionTypeV1) -> Self {
+        match junction_type {
+            JunctionTypeV1::None => JunctionType::None,
+            JunctionTypeV1::PowerPellet => JunctionType::PowerPellet,
+            JunctionTypeV1::GhostHouse => JunctionType::GhostHouse,
+            JunctionTypeV1::BonusItem => JunctionType::BonusItem,
+        }
+    }
+}
+
+// Version 2: links as (Junction, Slot) pairs, and everyone allowed through everywhere.
+#[derive(Deserialize)]
+struct LevelFileV2 {
+    max_connections: usize,
+    #[serde(default)]
+    info: LevelInfoV2,
+    junctions: Vec<JunctionV1>, // Junctions haven't changed shape since version 1.
+    links: Vec<(LinkEnd, LinkEnd)>,
+    #[serde(default)]
+    passages: Vec<(LinkEnd, PassageV2)>, // Some files slipped these in before the version got bumped.
+}
+
+#[derive(Deserialize)]
+struct LevelInfoV2 {
+    name: String,
+    size: Vec2,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    par_time: Option<f32>,
+    #[serde(default)]
+    rules: LevelRulesV2,
+}
+
+impl Default for LevelInfoV2 {
+    fn default() -> Self {
+        LevelInfoV2 {
+            name: "Untitled".to_string(),
+            size: DEFAULT_LEVEL_SIZE,
+            author: String::new(),
+            description: String::new(),
+            par_time: None,
+            rules: LevelRulesV2::default(),
+        }
+    }
+}
+
+impl From<LevelInfoV2> for components::LevelInfo {
+    fn from(info: LevelInfoV2) -> Self {
+        components::LevelInfo {
+            name: info.name,
+            size: info.size,
+            author: info.author,
+            description: info.description,
+            par_time: info.par_time,
+            rules: components::LevelRules {
+                agent_speed: info.rules.agent_speed,
+                frightened_duration: info.rules.frightened_duration,
+                ghost_roster: info
+                    .rules
+                    .ghost_roster
+                    .map(|roster| roster.into_iter().map(Into::into).collect()),
+                ghost_tunnel_speed: info.rules.ghost_tunnel_speed,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct LevelRulesV2 {
+    #[serde(default)]
+    agent_speed: Option<f32>,
+    #[serde(default)]
+    frightened_duration: Option<f32>,
+    #[serde(default)]
+    ghost_roster: Option<Vec<NavAgentTypeV2>>,
+    #[serde(default)]
+    ghost_tunnel_speed: Option<f32>,
+}
+
+#[derive(Deserialize)]
+enum NavAgentTypeV2 {
+    Player,
+    Shadow,
+    Sneaky,
+    Moody,
+    Pokey,
+    Fearful,
+}
+
+impl From<NavAgentTypeV2> for components::NavAgentType {
+    fn from(agent_type: NavAgentTypeV2) -> Self {
+        match agent_type {
+            NavAgentTypeV2::Player => components::NavAgentType::Player,
+            NavAgentTypeV2::Shadow => components::NavAgentType::Shadow,
+            NavAgentTypeV2::Sneaky => components::NavAgentType::Sneaky,
+            NavAgentTypeV2::Moody => components::NavAgentType::Moody,
+            NavAgentTypeV2::Pokey => components::NavAgentType::Pokey,
+            NavAgentTypeV2::Fearful => components::NavAgentType::Fearful,
+        }
+    }
+}
+
+// One bit per agent type, in the order NavAgentTypeV2 lists them.
+#[derive(Deserialize)]
+struct AgentMaskV2(u8);
+
+impl From<AgentMaskV2> for components::AgentMask {
+    fn from(mask: AgentMaskV2) -> Self {
+        components::AgentMask(mask.0)
+    }
+}
+
+#[derive(Deserialize)]
+struct PassageV2 {
+    allowed: AgentMaskV2,
+    #[serde(default)]
+    kind: PassageKindV2,
+}
+
+#[derive(Deserialize, Default)]
+enum PassageKindV2 {
+    #[default]
+    Corridor,
+    Tunnel,
+}
+
+impl From<PassageV2> for components::Passage {
+    fn from(passage: PassageV2) -> Self {
+        components::Passage {
+            allowed: passage.allowed.into(),
+            kind: match passage.kind {
+                PassageKindV2::Corridor => components::PassageKind::Corridor,
+                PassageKindV2::Tunnel => components::PassageKind::Tunnel,
+            },
+        }
+    }
+}
+
+// Version 1 to version 2: turning the flat slot indices into (Junction, Slot) links.
+fn from_v1(level: LevelFileV1) -> Result<LevelFileV2, LevelFileError> {
+    let slots = level.max_connections;
+    if level.connections.len() != level.junctions.len() * slots {
+        return Err(LevelFileError::BadConnectionCount(
+            level.junctions.len() * slots,
+            level.connections.len(),
+        ));
+    }
+
+    // Each link gets listed from its lower end. One-sided links only have the one end, so they get listed from there.
+    // (If the other end's busy with a different link, check() turns that into an error rather than picking one.)
+    let mut links = Vec::new();
+    for (slot, connection) in level.connections.iter().enumerate() {
+        if let Some(other_slot) = connection.filter(|other_slot| {
+            slot < *other_slot || level.connections.get(*other_slot) != Some(&Some(slot))
+        }) {
+            if other_slot >= level.connections.len() {
+                return Err(LevelFileError::DanglingLink((
+                    other_slot / slots,
+                    other_slot % slots,
+                )));
+            }
+            links.push((
+                (slot / slots, slot % slots),
+                (other_slot / slots, other_slot % slots),
+            ));
+        }
+    }
+
+    Ok(LevelFileV2 {
+        max_connections: slots,
+        info: LevelInfoV2 {
+            name: level.info.name,
+            size: level.info.size,
+            ..default()
+        },
+        junctions: level.junctions,
+        links,
+        passages: Vec::new(), // Everything was open to everyone back then.
+    })
+}
+
+// Version 2 to version 3: who can go through each link, and which links are tunnels.
+fn from_v2(level: LevelFileV2) -> LevelFile {
+    LevelFile {
+        version: LEVEL_FORMAT_VERSION,
+        max_connections: level.max_connections,
+        info: level.info.into(),
+        junctions: level
+            .junctions
+            .into_iter()
+            .map(|junction| components::Junction(junction.0, junction.1.into()))
+            .collect(),
+        links: level.links,
+        passages: level
+            .passages
+            .into_iter()
+            .map(|(end, passage)| (end, passage.into()))
+            .collect(),
+    }
+}
+
+// Moving every link onto the current MAX_CONNECTIONS ring, keeping each one pointing as close to its old direction as possible.
+// If a junction ends up with more links than slots, the extras get dropped (with a warning).
+pub fn repack_slots(level: LevelFile) -> LevelFile {
+    let old_slots = level.max_connections.max(1);
+    let mut used = vec![false; level.junctions.len() * MAX_CONNECTIONS];
+
+    let mut links = Vec::new();
+    let mut moved = HashMap::new(); // Where each old slot ended up, so the passages can follow.
+    for (first, second) in level.links {
+        let placed_first = place_slot(&mut used, old_slots, first);
+        let placed_second = place_slot(&mut used, old_slots, second);
+        match (placed_first, placed_second) {
+            (Some(placed_first), Some(placed_second)) => {
+                moved.insert(first, placed_first);
+                moved.insert(second, placed_second);
+                links.push((placed_first, placed_second));
+            }
+            _ => {
+                // Giving back whichever end did fit.
+                for (junction, slot) in placed_first.into_iter().chain(placed_second) {
+                    used[junction * MAX_CONNECTIONS + slot] = false;
+                }
+                warn!(
+                    "Dropped the link between junction {} and junction {}, there's no room for it.",
+                    first.0, second.0
+                );
+            }
+        }
+    }
+
+    let passages = level
+        .passages
+        .iter()
+        .filter_map(|(end, passage)| Some((*moved.get(end)?, *passage)))
+        .collect();
+
+    LevelFile {
+        max_connections: MAX_CONNECTIONS,
+        links,
+        passages,
+        ..level
+    }
+}
+
+// Finding the free slot closest to where an old slot was pointing, and claiming it.
+fn place_slot(used: &mut [bool], old_slots: usize, (junction, slot): LinkEnd) -> Option<LinkEnd> {
+    if (junction + 1) * MAX_CONNECTIONS > used.len() {
+        return None;
+    }
+    let nearest = (slot as f32 / old_slots as f32 * MAX_CONNECTIONS as f32).round() as usize;
+    (0..MAX_CONNECTIONS)
+        .flat_map(|offset| [nearest + offset, nearest + MAX_CONNECTIONS - offset])
+        .map(|candidate| candidate % MAX_CONNECTIONS)
+        .find(|candidate| !used[junction * MAX_CONNECTIONS + candidate])
+        .map(|candidate| {
+            used[junction * MAX_CONNECTIONS + candidate] = true;
+            (junction, candidate)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::level::file::VersionProbe;
+
+    const LEVEL_V1: &str = include_str!("../../../tests/fixtures/level_v1.ron");
+    const LEVEL_V2: &str = include_str!("../../../tests/fixtures/level_v2.ron");
+
+    // Reading a fixture the same way a level file gets read.
+    fn read(text: &str) -> LevelFile {
+        let probe: VersionProbe = ron::from_str(text).unwrap();
+        migrate(probe.version, text).unwrap()
+    }
+
+    #[test]
+    fn migrates_v1() {
+        let level = read(LEVEL_V1);
+        assert_eq!(level.version, LEVEL_FORMAT_VERSION);
+        assert_eq!(level.info.name, "Old Timer");
+        assert_eq!(
+            level.junctions,
+            vec![
+                components::Junction(Vec2::new(0.0, 0.0), JunctionType::BonusItem),
+                components::Junction(Vec2::new(100.0, 0.0), JunctionType::GhostHouse),
+                components::Junction(Vec2::new(0.0, 100.0), JunctionType::PowerPellet),
+            ]
+        );
+        // The one-sided link makes it through too.
+        assert_eq!(
+            level.links,
+            vec![((0, 0), (1, 4)), ((0, 2), (2, 6)), ((2, 7), (1, 3))]
+        );
+        assert!(level.passages.is_empty());
+        assert!(level.check().is_ok());
+    }
+
+    #[test]
+    fn migrates_v2() {
+        let level = read(LEVEL_V2);
+        assert_eq!(level.version, LEVEL_FORMAT_VERSION);
+        assert_eq!(level.info.name, "Middle Child");
+        assert_eq!(level.info.author, "Someone");
+        assert_eq!(level.info.rules.frightened_duration, Some(4.0));
+        assert_eq!(
+            level.info.rules.ghost_roster,
+            Some(vec![
+                components::NavAgentType::Shadow,
+                components::NavAgentType::Pokey
+            ])
+        );
+        assert_eq!(level.links, vec![((0, 0), (1, 4)), ((0, 2), (2, 6))]);
+        assert_eq!(
+            level.passages,
+            vec![(
+                (0, 2),
+                components::Passage {
+                    allowed: components::AgentMask::NONE,
+                    kind: components::PassageKind::Tunnel,
+                }
+            )]
+        );
+        assert!(level.check().is_ok());
+    }
+
+    #[test]
+    fn current_version_reads_as_is() {
+        let level = read(LEVEL_V2);
+        let text = level.to_ron().unwrap();
+        assert_eq!(read(&text), level);
+    }
+
+    #[test]
+    fn unknown_version() {
+        assert!(matches!(
+            migrate(LEVEL_FORMAT_VERSION + 1, ""),
+            Err(LevelFileError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn one_sided_link_into_a_busy_slot() {
+        // Junction 1 slot 0 points one-sidedly at junction 0 slot 0, which is already linked to junction 1 slot 4.
+        let text = LEVEL_V1.replace("Some(12), None,", "Some(12), Some(8),");
+        let text = text.replace(
+            "None, None, None, None, Some(0),",
+            "Some(0), None, None, None, Some(0),",
+        );
+        let level = read(&text);
+        assert!(matches!(level.check(), Err(LevelFileError::SlotTaken(_))));
+    }
+
+    // A level saved with four slots a junction, pointing right, up, left and down.
+    fn four_slot_level() -> LevelFile {
+        LevelFile {
+            version: LEVEL_FORMAT_VERSION,
+            max_connections: 4,
+            info: components::LevelInfo::default(),
+            junctions: vec![
+                components::Junction(Vec2::new(0.0, 0.0), JunctionType::BonusItem),
+                components::Junction(Vec2::new(100.0, 0.0), JunctionType::GhostHouse),
+                components::Junction(Vec2::new(0.0, 100.0), JunctionType::None),
+            ],
+            links: vec![((0, 0), (1, 2)), ((0, 1), (2, 3))],
+            passages: vec![(
+                (0, 1),
+                components::Passage {
+                    allowed: components::AgentMask::GHOSTS,
+                    kind: components::PassageKind::Corridor,
+                },
+            )],
+        }
+    }
+
+    #[test]
+    fn repacks_fewer_slots() {
+        let level = repack_slots(four_slot_level());
+        assert_eq!(level.max_connections, MAX_CONNECTIONS);
+        let scale = MAX_CONNECTIONS / 4;
+        assert_eq!(
+            level.links,
+            vec![((0, 0), (1, 2 * scale)), ((0, scale), (2, 3 * scale))]
+        );
+        assert_eq!(level.passages[0].0, (0, scale));
+        assert!(level.check().is_ok());
+    }
+
+    #[test]
+    fn repacks_through_from_ron() {
+        let text = four_slot_level().to_ron().unwrap();
+        let level = LevelFile::from_ron(&text).unwrap();
+        assert_eq!(level, repack_slots(four_slot_level()));
+    }
+
+    #[test]
+    fn repacking_more_slots_drops_what_doesnt_fit() {
+        // Twice as many slots as there's room for, all on junction 0.
+        let slots = MAX_CONNECTIONS * 2;
+        let level = LevelFile {
+            version: LEVEL_FORMAT_VERSION,
+            max_connections: slots,
+            info: components::LevelInfo::default(),
+            junctions: (0..=slots)
+                .map(|index| components::Junction(Vec2::splat(index as f32), JunctionType::None))
+                .collect(),
+            links: (0..slots).map(|slot| ((0, slot), (slot + 1, 0))).collect(),
+            passages: Vec::new(),
+        };
+        let level = repack_slots(level);
+        assert_eq!(level.links.len(), MAX_CONNECTIONS);
+        assert!(level.check().is_ok());
+    }
+}
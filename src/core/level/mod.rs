@@ -0,0 +1,313 @@
+use crate::components;
+use crate::core::editor::playtest::PlayTest;
+use crate::core::launch::LaunchOptions;
+use crate::core::level::ascii::import_ascii_file;
+use crate::core::level::file::LevelFile;
+use crate::core::level::pack::{CurrentPack, LevelPack, PackEnding};
+use crate::core::level::templates::LevelTemplate;
+use crate::core::level::validation::can_play;
+use crate::core::prelude::*;
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+
+pub(super) struct LevelPlugin;
+
+pub mod ascii; // Tile mazes, the old fashioned way.
+pub mod file; // Getting levels on and off the disk.
+pub mod generator; // Fresh mazes, made up on the spot.
+pub mod graph; // Handy bits for poking at junctions and connections.
+pub mod hot_reload; // Picking up changes to the level file while we're running.
+pub mod migrations; // Bringing old level files up to date.
+pub mod pack; // Whole sets of levels, one after the other.
+pub mod svg; // Pictures of levels, for showing off outside the game.
+pub mod templates; // Premade levels to start from.
+pub mod validation; // Making sure a level is actually playable.
+
+#[derive(Resource)]
+pub struct LevelPath(pub PathBuf); // Where the current level lives on disk.
+
+#[derive(Event)]
+pub struct LevelReplaced; // The level got swapped out from under everyone, so anything pointing into it should let go.
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<components::LevelInfo>()
+            .insert_resource(components::Junctions(Vec::new()))
+            .insert_resource(components::Connections::default())
+            .insert_resource(LevelPath(PathBuf::from(DEFAULT_LEVEL_PATH)))
+            .init_asset::<hot_reload::LevelAsset>()
+            .init_asset_loader::<hot_reload::LevelAssetLoader>()
+            .init_resource::<hot_reload::WatchedLevel>()
+            .add_event::<LevelReplaced>()
+            .add_systems(
+                Startup,
+                (load_pack, load_level, load_ascii, load_generated).chain(),
+            )
+            .add_systems(Update, (hot_reload::watch_level, hot_reload::reload_level))
+            // Test plays head back to the editor instead.
+            .add_systems(
+                OnEnter(GameState::Playing(Playing::Complete)),
+                advance_pack.run_if(|play_test: Res<PlayTest>| play_test.0.is_none()),
+            );
+        // .add_systems(OnEnter(GameState::Playing(Playing::New)), level_build);
+    }
+}
+
+// Picking up a level pack, if there is one, and pointing the level at its first entry.
+fn load_pack(
+    mut commands: Commands,
+    launch_options: Res<LaunchOptions>,
+    mut level_path: ResMut<LevelPath>,
+) {
+    // Asked for a specific level? Then never mind the pack.
+    if let Some(launch_level) = &launch_options.level {
+        level_path.0 = launch_level.clone();
+        return;
+    }
+
+    let pack_path = PathBuf::from(DEFAULT_PACK_PATH);
+
+    // No pack? We'll just be editing the one level then.
+    if !pack_path.exists() {
+        return;
+    }
+
+    match LevelPack::load(&pack_path) {
+        Ok(pack) => {
+            let current_pack = CurrentPack {
+                pack,
+                path: pack_path,
+                index: 0,
+            };
+            if let Some(entry_path) = current_pack.entry_path() {
+                level_path.0 = entry_path;
+            }
+            info!(
+                "Loaded level pack \"{}\" with {} levels.",
+                current_pack.pack.name,
+                current_pack.pack.levels.len()
+            );
+            commands.insert_resource(current_pack);
+        }
+        Err(error) => error!("Couldn't load {}: {error}", pack_path.display()),
+    }
+}
+
+// Pulling the last saved level off the disk, if there is one.
+fn load_level(
+    mut level_path: ResMut<LevelPath>,
+    current_pack: Option<Res<CurrentPack>>,
+    mut level_info: ResMut<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+) {
+    if level_path.0 == Path::new(DEFAULT_LEVEL_PATH) {
+        level_path.0 = default_level_path();
+    }
+
+    // Nothing saved yet? Just start empty.
+    if !level_path.0.exists() {
+        return;
+    }
+
+    read_level(
+        &level_path.0,
+        current_pack.as_deref(),
+        &mut level_info,
+        &mut junctions,
+        &mut connections,
+    );
+}
+
+// Where the default level lives. Levels saved before it needed the .level.ron extension get moved over first.
+// If moving it doesn't work out, it just gets read from where it is.
+fn default_level_path() -> PathBuf {
+    let (path, legacy_path) = (Path::new(DEFAULT_LEVEL_PATH), Path::new(LEGACY_LEVEL_PATH));
+    if path.exists() || !legacy_path.exists() {
+        return path.to_path_buf();
+    }
+    match std::fs::rename(legacy_path, path) {
+        Ok(()) => {
+            info!(
+                "Moved {} over to {}.",
+                legacy_path.display(),
+                path.display()
+            );
+            path.to_path_buf()
+        }
+        Err(error) => {
+            warn!("Couldn't move {} over: {error}", legacy_path.display());
+            legacy_path.to_path_buf()
+        }
+    }
+}
+
+// Starting from a tile maze, if one was asked for on the command line.
+fn load_ascii(
+    launch_options: Res<LaunchOptions>,
+    mut level_info: ResMut<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+) {
+    let Some(ascii_path) = &launch_options.import_ascii else {
+        return;
+    };
+
+    match import_ascii_file(ascii_path, ASCII_TILE_SIZE) {
+        Ok(level) => {
+            (*junctions, *connections) = level;
+            level_info.name = ascii_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            info!("Imported maze from {}.", ascii_path.display());
+        }
+        Err(error) => error!("Couldn't import {}: {error}", ascii_path.display()),
+    }
+}
+
+// Starting from a generated maze, if one was asked for on the command line.
+fn load_generated(
+    launch_options: Res<LaunchOptions>,
+    mut level_info: ResMut<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+) {
+    let Some(settings) = launch_options.maze_settings() else {
+        return;
+    };
+
+    (*junctions, *connections) = LevelTemplate::Generated(settings).build(DEFAULT_LEVEL_SIZE);
+    *level_info = components::LevelInfo {
+        name: format!("Maze {}", settings.seed),
+        size: DEFAULT_LEVEL_SIZE,
+        ..default()
+    };
+    info!("Generated maze from {settings:?}.");
+}
+
+// Drawing a level out to an SVG without ever opening a window. Gives back an exit code.
+pub fn export_svg_headless(launch_options: &LaunchOptions) -> i32 {
+    let Some(svg_path) = &launch_options.export_svg else {
+        return 0;
+    };
+
+    // A generated maze if one was asked for, then a tile maze if one was given, otherwise the level file.
+    let level = match (launch_options.maze_settings(), &launch_options.import_ascii) {
+        (Some(settings), _) => Ok(LevelTemplate::Generated(settings).build(DEFAULT_LEVEL_SIZE)),
+        (None, Some(ascii_path)) => import_ascii_file(ascii_path, ASCII_TILE_SIZE)
+            .map_err(|error| format!("Couldn't import {}: {error}", ascii_path.display())),
+        (None, None) => {
+            let level_path = launch_options
+                .level
+                .clone()
+                .unwrap_or_else(default_level_path);
+            LevelFile::load(&level_path)
+                .map(|level| {
+                    let (_, junctions, connections) = level.into_level();
+                    (junctions, connections)
+                })
+                .map_err(|error| format!("Couldn't load {}: {error}", level_path.display()))
+        }
+    };
+
+    match level.and_then(|(junctions, connections)| {
+        svg::save_svg(
+            svg_path,
+            &junctions,
+            &connections,
+            launch_options.svg_labels,
+        )
+        .map_err(|error| format!("Couldn't write {}: {error}", svg_path.display()))
+    }) {
+        Ok(()) => {
+            println!("Wrote {}.", svg_path.display());
+            0
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            1
+        }
+    }
+}
+
+// Level beaten! On to the next one in the pack.
+fn advance_pack(
+    current_pack: Option<ResMut<CurrentPack>>,
+    mut level_path: ResMut<LevelPath>,
+    mut level_info: ResMut<components::LevelInfo>,
+    mut junctions: ResMut<components::Junctions>,
+    mut connections: ResMut<components::Connections>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Just the one level? Then that's the whole game.
+    let Some(mut current_pack) = current_pack else {
+        next_state.set(GameState::Menu(Menu::Victory));
+        return;
+    };
+
+    current_pack.index += 1;
+    if current_pack.index >= current_pack.pack.levels.len() {
+        match current_pack.pack.ending {
+            PackEnding::Victory => {
+                next_state.set(GameState::Menu(Menu::Victory));
+                return;
+            }
+            PackEnding::Loop => current_pack.index = 0,
+        }
+    }
+
+    // Loading up the next one and getting it going.
+    if let Some(entry_path) = current_pack.entry_path() {
+        level_path.0 = entry_path;
+    }
+    if read_level(
+        &level_path.0,
+        Some(&current_pack),
+        &mut level_info,
+        &mut junctions,
+        &mut connections,
+    ) && can_play(&junctions, &connections)
+    {
+        next_state.set(GameState::Playing(Playing::New));
+    } else {
+        next_state.set(GameState::Editor(Editor::Nodes)); // Something's wrong with it. Go fix it.
+    }
+}
+
+// Swapping the level resources out for whatever's in a file. Returns whether it worked.
+fn read_level(
+    path: &Path,
+    current_pack: Option<&CurrentPack>,
+    level_info: &mut components::LevelInfo,
+    junctions: &mut components::Junctions,
+    connections: &mut components::Connections,
+) -> bool {
+    match LevelFile::load(path) {
+        Ok(level) => {
+            (*level_info, *junctions, *connections) = level.into_level();
+
+            // The pack gets the final say on what a level's called.
+            if let Some(name) = current_pack
+                .and_then(|current_pack| current_pack.entry())
+                .and_then(|entry| entry.name.clone())
+            {
+                level_info.name = name;
+            }
+
+            info!("Loaded level from {}.", path.display());
+            true
+        }
+        Err(error) => {
+            error!("Couldn't load {}: {error}", path.display());
+            false
+        }
+    }
+}
+
+// fn level_build(junctions: ResMut<components::Junctions>) {
+//     // First load the level...
+//     // Uh... That's a todo.
+
+//     // Then spawn a graphic for each thingy.
+// }
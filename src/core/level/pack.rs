@@ -0,0 +1,62 @@
+use crate::core::level::file::LevelFileError;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// What happens once the last level in a pack is beaten.
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum PackEnding {
+    #[default]
+    Victory, // Roll the victory screen.
+    Loop, // Back to the first level, forever and ever.
+}
+
+// A single level in a pack.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PackEntry {
+    pub path: PathBuf, // Where the level file is, relative to the pack file.
+    #[serde(default)]
+    pub name: Option<String>, // What to call the level, if not what the level calls itself.
+    #[serde(default)]
+    pub description: String, // A little blurb about the level.
+}
+
+// A whole set of levels, played in order.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct LevelPack {
+    pub name: String, // What the pack's called.
+    #[serde(default)]
+    pub ending: PackEnding, // What to do after the last level.
+    pub levels: Vec<PackEntry>, // The levels, first to last.
+}
+
+impl LevelPack {
+    // Reading a pack off the disk.
+    pub fn load(path: &Path) -> Result<Self, LevelFileError> {
+        ron::from_str(&std::fs::read_to_string(path)?).map_err(LevelFileError::Deserialize)
+    }
+}
+
+#[derive(Resource)]
+pub struct CurrentPack {
+    pub pack: LevelPack, // The pack being played.
+    pub path: PathBuf,   // Where the pack file lives.
+    pub index: usize,    // Which of its levels we're on.
+}
+
+impl CurrentPack {
+    // The level we're on right now.
+    pub fn entry(&self) -> Option<&PackEntry> {
+        self.pack.levels.get(self.index)
+    }
+
+    // Where the current level's file is, resolved against the pack file's folder.
+    pub fn entry_path(&self) -> Option<PathBuf> {
+        self.entry().map(|entry| {
+            self.path
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(&entry.path)
+        })
+    }
+}
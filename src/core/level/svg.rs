@@ -0,0 +1,261 @@
+use crate::components;
+use crate::core::level::graph;
+use crate::core::prelude::*;
+use bevy::prelude::*;
+use std::fmt::Write;
+use std::path::Path;
+
+// How much empty room to leave around the level.
+const SVG_MARGIN: f32 = EDITOR_JUNCTION_RADIUS * 2.0;
+// How long the arrowheads on restricted links are.
+const SVG_ARROW_SIZE: f32 = 10.0;
+
+// Drawing the level out as an SVG, the same way the editor draws it.
+// SVG goes down where we go up, so every y gets flipped on the way out.
+pub fn level_to_svg(
+    junctions: &components::Junctions,
+    connections: &components::Connections,
+    labels: bool,
+) -> String {
+    // Finding how much room the level takes up.
+    let (minimum, maximum) = junctions.0.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(minimum, maximum), junction| {
+            let flipped = Vec2::new(junction.0.x, -junction.0.y);
+            (minimum.min(flipped), maximum.max(flipped))
+        },
+    );
+    let (minimum, maximum) = if junctions.0.is_empty() {
+        (Vec2::ZERO, Vec2::ZERO) // Nothing to draw. Still gotta be a valid picture though.
+    } else {
+        (minimum, maximum)
+    };
+    let origin = minimum - SVG_MARGIN;
+    let size = maximum - minimum + SVG_MARGIN * 2.0;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        origin.x, origin.y, size.x, size.y, size.x, size.y
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="black"/>"#,
+        origin.x, origin.y, size.x, size.y
+    );
+
+    // The connections first, so the junctions sit on top of them. Same colors and arrows as the editor.
+    let _ = writeln!(svg, r#"<g fill="none" stroke-width="2">"#);
+    for (slot, connection) in connections.0.iter().enumerate() {
+        // Only drawing each link the once, and only if it goes somewhere real.
+        let Some(other_slot) = connection.filter(|other_slot| {
+            slot < *other_slot && other_slot / MAX_CONNECTIONS < junctions.0.len()
+        }) else {
+            continue;
+        };
+        let ends = [slot, other_slot];
+        let passages = ends.map(|end| connections.1.get(end).copied().unwrap_or_default());
+        let colors = passages.map(|passage| passage_color(passage).to_hex());
+        let starts =
+            ends.map(|end| graph::slot_position(junctions.0[end / MAX_CONNECTIONS].0, end));
+
+        // Where each end's half of the link stops. Corridors meet in the middle, tunnels just poke out the side.
+        let tunnel = passages[0].kind == components::PassageKind::Tunnel;
+        let stops = if tunnel {
+            ends.map(|end| {
+                junctions.0[end / MAX_CONNECTIONS].0
+                    + graph::slot_direction(end) * TUNNEL_MOUTH_DEPTH
+            })
+        } else {
+            [(starts[0] + starts[1]) * 0.5; 2]
+        };
+
+        if tunnel {
+            // A faint line to where it comes out, then each mouth.
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-dasharray="8 6"/>"#,
+                stops[0].x,
+                -stops[0].y,
+                stops[1].x,
+                -stops[1].y,
+                bevy::color::palettes::css::DARK_SLATE_GRAY.to_hex()
+            );
+            for ((start, stop), color) in starts.iter().zip(stops).zip(&colors) {
+                let _ = writeln!(
+                    svg,
+                    r#"<polyline points="{},{} {},{}" stroke="{color}"/>"#,
+                    start.x, -start.y, stop.x, -stop.y
+                );
+            }
+        } else {
+            // The whole way across, and the far half again if that end's allowed through differently.
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}"/>"#,
+                starts[0].x, -starts[0].y, starts[1].x, -starts[1].y, colors[0]
+            );
+            if colors[1] != colors[0] {
+                let _ = writeln!(
+                    svg,
+                    r#"<polyline points="{},{} {},{}" stroke="{}"/>"#,
+                    starts[1].x, -starts[1].y, stops[1].x, -stops[1].y, colors[1]
+                );
+            }
+        }
+
+        // Restricted ends (and the open end of one-way links) get an arrow, pointing the way they let agents through.
+        for (end, other_end) in [(0, 1), (1, 0)] {
+            if passages[end].allowed != components::AgentMask::NONE
+                && (passages[end].allowed != components::AgentMask::ALL
+                    || passages[other_end].allowed == components::AgentMask::NONE)
+            {
+                let direction = (stops[end] - starts[end]).normalize_or_zero();
+                let (tip, back) = (stops[end], stops[end] - direction * SVG_ARROW_SIZE);
+                let side = direction.perp() * SVG_ARROW_SIZE * 0.5;
+                let _ = writeln!(
+                    svg,
+                    r#"<polygon points="{},{} {},{} {},{}" fill="{}"/>"#,
+                    tip.x,
+                    -tip.y,
+                    (back + side).x,
+                    -(back + side).y,
+                    (back - side).x,
+                    -(back - side).y,
+                    colors[end]
+                );
+            }
+        }
+    }
+    let _ = writeln!(svg, "</g>");
+
+    // Then the junctions and their slots.
+    let _ = writeln!(svg, r#"<g fill="none" stroke-width="2">"#);
+    for (junction_index, junction) in junctions.0.iter().enumerate() {
+        for slot in 0..MAX_CONNECTIONS {
+            let slot_position = graph::slot_position(junction.0, slot);
+            let used = connections
+                .0
+                .get(junction_index * MAX_CONNECTIONS + slot)
+                .is_some_and(|connection| connection.is_some());
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" stroke="{}"/>"#,
+                slot_position.x,
+                -slot_position.y,
+                EDITOR_JUNCTION_CONNECTION_POINT_RADIUS,
+                if used {
+                    bevy::color::palettes::css::GREEN.to_hex()
+                } else {
+                    bevy::color::palettes::css::GREY.to_hex()
+                }
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{}" cy="{}" r="{}" stroke="{}"/>"#,
+            junction.0.x,
+            -junction.0.y,
+            EDITOR_JUNCTION_RADIUS,
+            junction.1.color().to_hex()
+        );
+    }
+    let _ = writeln!(svg, "</g>");
+
+    // And the indices, if they're wanted.
+    if labels {
+        let _ = writeln!(
+            svg,
+            r#"<g fill="white" font-family="monospace" font-size="14" text-anchor="middle" dominant-baseline="central">"#
+        );
+        for (junction_index, junction) in junctions.0.iter().enumerate() {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}">{junction_index}</text>"#,
+                junction.0.x, -junction.0.y
+            );
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+
+    let _ = writeln!(svg, "</svg>");
+    svg
+}
+
+// What color a link end gets drawn in, same as the editor. Tunnels open to everyone stand out from the corridors.
+fn passage_color(passage: components::Passage) -> Srgba {
+    match (passage.kind, passage.allowed) {
+        (components::PassageKind::Tunnel, components::AgentMask::ALL) => {
+            bevy::color::palettes::css::DEEP_SKY_BLUE
+        }
+        (_, allowed) => allowed.color(),
+    }
+}
+
+// Writing the picture out to disk, making the folder if it isn't there yet.
+pub fn save_svg(
+    path: &Path,
+    junctions: &components::Junctions,
+    connections: &components::Connections,
+    labels: bool,
+) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, level_to_svg(junctions, connections, labels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::level::templates::LevelTemplate;
+
+    fn count(svg: &str, pattern: &str) -> usize {
+        svg.matches(pattern).count()
+    }
+
+    #[test]
+    fn one_of_everything() {
+        let (junctions, connections) = LevelTemplate::Classic.build(DEFAULT_LEVEL_SIZE);
+        let links = connections.0.iter().flatten().count() / 2;
+        let svg = level_to_svg(&junctions, &connections, false);
+
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(count(&svg, "<line"), links);
+        assert_eq!(
+            count(&svg, &format!(r#"r="{EDITOR_JUNCTION_RADIUS}""#)),
+            junctions.0.len()
+        );
+        assert_eq!(count(&svg, "<text"), 0);
+
+        let labelled = level_to_svg(&junctions, &connections, true);
+        assert_eq!(count(&labelled, "<text"), junctions.0.len());
+    }
+
+    #[test]
+    fn tunnels_and_one_way_links_stand_out() {
+        let (junctions, mut connections) = LevelTemplate::SquareLoop.build(DEFAULT_LEVEL_SIZE);
+        let plain = level_to_svg(&junctions, &connections, false);
+        assert_eq!(count(&plain, "stroke-dasharray"), 0);
+        assert_eq!(count(&plain, "<polygon"), 0);
+
+        // One link turned into a tunnel, and another made one-way.
+        let mut links = (0..connections.0.len())
+            .filter(|slot| connections.0[*slot].is_some_and(|other_slot| *slot < other_slot));
+        let (tunnel, one_way) = (links.next().unwrap(), links.next().unwrap());
+        for end in [tunnel, connections.0[tunnel].unwrap()] {
+            connections.1[end].kind = components::PassageKind::Tunnel;
+        }
+        connections.1[one_way].allowed = components::AgentMask::NONE;
+
+        let svg = level_to_svg(&junctions, &connections, false);
+        assert_eq!(count(&svg, "stroke-dasharray"), 1);
+        assert!(svg.contains(&bevy::color::palettes::css::DEEP_SKY_BLUE.to_hex()));
+        assert_eq!(count(&svg, "<polygon"), 1); // Just the end that still lets agents out.
+        assert_eq!(
+            count(&svg, "<line"),
+            connections.0.iter().flatten().count() / 2
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use crate::components::{self, JunctionType};
+use crate::core::level::{
+    generator::{self, MazeSettings},
+    graph,
+};
+use bevy::prelude::*;
+
+// The starting points the new level wizard offers.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum LevelTemplate {
+    Empty,                   // A blank slate.
+    Classic,                 // Something close to the arcade maze.
+    SquareLoop,              // Four corners, all wired up.
+    Grid(usize, usize),      // A grid of (columns, rows) junctions.
+    Generated(MazeSettings), // A random maze, different for every seed.
+}
+
+impl LevelTemplate {
+    // The next template along, for cycling through them.
+    pub fn next(&self) -> Self {
+        match self {
+            LevelTemplate::Empty => LevelTemplate::Classic,
+            LevelTemplate::Classic => LevelTemplate::SquareLoop,
+            LevelTemplate::SquareLoop => LevelTemplate::Grid(4, 3),
+            LevelTemplate::Grid(_, _) => LevelTemplate::Generated(MazeSettings::default()),
+            LevelTemplate::Generated(_) => LevelTemplate::Empty,
+        }
+    }
+
+    // And the one before it.
+    pub fn previous(&self) -> Self {
+        match self {
+            LevelTemplate::Empty => LevelTemplate::Generated(MazeSettings::default()),
+            LevelTemplate::Classic => LevelTemplate::Empty,
+            LevelTemplate::SquareLoop => LevelTemplate::Classic,
+            LevelTemplate::Grid(_, _) => LevelTemplate::SquareLoop,
+            LevelTemplate::Generated(_) => LevelTemplate::Grid(4, 3),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LevelTemplate::Empty => "Empty",
+            LevelTemplate::Classic => "Classic",
+            LevelTemplate::SquareLoop => "Square Loop",
+            LevelTemplate::Grid(_, _) => "Grid",
+            LevelTemplate::Generated(_) => "Generated",
+        }
+    }
+
+    // Building the template out, stretched to fit the given size and centered on the origin.
+    pub fn build(&self, size: Vec2) -> (components::Junctions, components::Connections) {
+        let mut junctions = components::Junctions(Vec::new());
+        let mut connections = components::Connections::default();
+
+        match *self {
+            LevelTemplate::Empty => {}
+            LevelTemplate::Classic => build_classic(&mut junctions, &mut connections, size),
+            LevelTemplate::SquareLoop => build_grid(&mut junctions, &mut connections, size, 2, 2),
+            LevelTemplate::Grid(columns, rows) => {
+                build_grid(&mut junctions, &mut connections, size, columns, rows)
+            }
+            LevelTemplate::Generated(settings) => {
+                generator::build_maze(&mut junctions, &mut connections, size, &settings)
+            }
+        }
+
+        (junctions, connections)
+    }
+}
+
+// A plain old grid, with every neighbor wired up.
+fn build_grid(
+    junctions: &mut components::Junctions,
+    connections: &mut components::Connections,
+    size: Vec2,
+    columns: usize,
+    rows: usize,
+) {
+    let spacing = size / Vec2::new(columns.max(2) as f32 - 1.0, rows.max(2) as f32 - 1.0);
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let index = graph::add_junction(
+                junctions,
+                connections,
+                Vec2::new(column as f32, row as f32) * spacing - size * 0.5,
+                JunctionType::None,
+            );
+
+            if column > 0 {
+                graph::link_junctions(junctions, connections, index - 1, index);
+            }
+            if row > 0 {
+                graph::link_junctions(junctions, connections, index - columns, index);
+            }
+        }
+    }
+}
+
+// The left half of the arcade maze, in tiles. (Column, Row, Type), with rows counting down from the top.
+const CLASSIC_HALF: [(f32, f32, JunctionType); 32] = [
+    (1.0, 1.0, JunctionType::None),         // 0
+    (6.0, 1.0, JunctionType::None),         // 1
+    (12.0, 1.0, JunctionType::None),        // 2
+    (1.0, 5.0, JunctionType::PowerPellet),  // 3
+    (6.0, 5.0, JunctionType::None),         // 4
+    (9.0, 5.0, JunctionType::None),         // 5
+    (12.0, 5.0, JunctionType::None),        // 6
+    (1.0, 8.0, JunctionType::None),         // 7
+    (6.0, 8.0, JunctionType::None),         // 8
+    (9.0, 8.0, JunctionType::None),         // 9
+    (12.0, 8.0, JunctionType::None),        // 10
+    (9.0, 11.0, JunctionType::None),        // 11
+    (12.0, 11.0, JunctionType::None),       // 12
+    (6.0, 14.0, JunctionType::None),        // 13
+    (9.0, 14.0, JunctionType::None),        // 14
+    (9.0, 17.0, JunctionType::None),        // 15
+    (1.0, 20.0, JunctionType::None),        // 16
+    (6.0, 20.0, JunctionType::None),        // 17
+    (9.0, 20.0, JunctionType::None),        // 18
+    (12.0, 20.0, JunctionType::None),       // 19
+    (1.0, 23.0, JunctionType::PowerPellet), // 20
+    (3.0, 23.0, JunctionType::None),        // 21
+    (6.0, 23.0, JunctionType::None),        // 22
+    (9.0, 23.0, JunctionType::None),        // 23
+    (12.0, 23.0, JunctionType::None),       // 24
+    (1.0, 26.0, JunctionType::None),        // 25
+    (3.0, 26.0, JunctionType::None),        // 26
+    (6.0, 26.0, JunctionType::None),        // 27
+    (9.0, 26.0, JunctionType::None),        // 28
+    (12.0, 26.0, JunctionType::None),       // 29
+    (1.0, 29.0, JunctionType::None),        // 30
+    (12.0, 29.0, JunctionType::None),       // 31
+];
+
+// How the left half is wired together.
+const CLASSIC_HALF_LINKS: [(usize, usize); 39] = [
+    (0, 1),
+    (1, 2),
+    (0, 3),
+    (1, 4),
+    (2, 6),
+    (3, 4),
+    (4, 5),
+    (5, 6),
+    (3, 7),
+    (4, 8),
+    (7, 8),
+    (5, 9),
+    (9, 10),
+    (10, 12),
+    (11, 12),
+    (8, 13),
+    (13, 14),
+    (11, 14),
+    (14, 15),
+    (15, 18),
+    (13, 17),
+    (16, 17),
+    (17, 18),
+    (18, 19),
+    (16, 20),
+    (20, 21),
+    (21, 26),
+    (17, 22),
+    (22, 23),
+    (23, 24),
+    (19, 24),
+    (25, 26),
+    (26, 27),
+    (22, 27),
+    (23, 28),
+    (28, 29),
+    (25, 30),
+    (29, 31),
+    (30, 31),
+];
+
+// Which left half junctions get wired straight across to their mirrored twin.
+const CLASSIC_CENTER_LINKS: [usize; 3] = [6, 15, 31];
+
+// The arcade maze, more or less. Built as a left half then mirrored across the middle.
+fn build_classic(
+    junctions: &mut components::Junctions,
+    connections: &mut components::Connections,
+    size: Vec2,
+) {
+    // Tiles to world space. The maze is 25 tiles wide and 28 tall between its outermost junctions.
+    let tile = size / Vec2::new(25.0, 28.0);
+    let to_world = |column: f32, row: f32| Vec2::new(column - 13.5, 15.0 - row) * tile;
+
+    // Both halves, left then right.
+    for (column, row, junction_type) in CLASSIC_HALF {
+        graph::add_junction(junctions, connections, to_world(column, row), junction_type);
+    }
+    for (column, row, junction_type) in CLASSIC_HALF {
+        graph::add_junction(
+            junctions,
+            connections,
+            to_world(27.0 - column, row),
+            junction_type,
+        );
+    }
+
+    // Wiring each half up the same way.
+    let mirror = CLASSIC_HALF.len();
+    for (first, second) in CLASSIC_HALF_LINKS {
+        graph::link_junctions(junctions, connections, first, second);
+        graph::link_junctions(junctions, connections, first + mirror, second + mirror);
+    }
+    for junction in CLASSIC_CENTER_LINKS {
+        graph::link_junctions(junctions, connections, junction, junction + mirror);
+    }
+
+    // The ghost house, sitting under its door in the middle.
+    let door = graph::add_junction(
+        junctions,
+        connections,
+        to_world(13.5, 11.0),
+        JunctionType::None,
+    );
+    let house = graph::add_junction(
+        junctions,
+        connections,
+        to_world(13.5, 14.0),
+        JunctionType::GhostHouse,
+    );
+    graph::link_junctions(junctions, connections, 12, door);
+    graph::link_junctions(junctions, connections, door, 12 + mirror);
+    graph::link_junctions(junctions, connections, door, house);
+
+    // And the player, down below it.
+    let spawn = graph::add_junction(
+        junctions,
+        connections,
+        to_world(13.5, 23.0),
+        JunctionType::BonusItem,
+    );
+    graph::link_junctions(junctions, connections, 24, spawn);
+    graph::link_junctions(junctions, connections, spawn, 24 + mirror);
+}
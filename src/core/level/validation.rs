@@ -0,0 +1,515 @@
+use crate::components::{self, JunctionType};
+use crate::core::prelude::*;
+use bevy::prelude::*;
+
+// Everything that can be wrong with a level.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum LevelIssue {
+    AsymmetricLink(usize, usize), // (Slot, Target) This slot points somewhere that doesn't point back.
+    DanglingIndex(usize, usize),  // (Slot, Target) This slot points past the end of the level.
+    UnreachableJunction(usize),   // Nobody can get to this junction from the player's spawn.
+    UnreachablePellet(usize), // The player can't ever get to this pellet, so the level can't be cleared.
+    MissingGhostHouse,        // There's nowhere for the ghosts to come from.
+    MissingPlayerSpawn,       // There's nowhere for the player to come from.
+    MultiplePlayerSpawns(Vec<usize>), // There's more than one place the player could come from.
+    ReversingDeadEnd(usize),  // Ghosts that go in here can only come back out by turning around.
+    IsolatedJunction(usize),  // Nothing links to this junction at all.
+    OverlappingJunctions(usize, usize), // These two junctions are sitting on top of each other.
+    CrossingLinks(usize, usize), // (Slot, Slot) These two links cross over each other.
+}
+
+impl LevelIssue {
+    // Errors stop the level from being played. Everything else is just a heads up.
+    pub fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            LevelIssue::ReversingDeadEnd(_)
+                | LevelIssue::IsolatedJunction(_)
+                | LevelIssue::OverlappingJunctions(_, _)
+                | LevelIssue::CrossingLinks(_, _)
+        )
+    }
+
+    // The junction to go look at, if there's one to blame.
+    pub fn junction(&self) -> Option<usize> {
+        match self {
+            LevelIssue::AsymmetricLink(slot, _)
+            | LevelIssue::DanglingIndex(slot, _)
+            | LevelIssue::CrossingLinks(slot, _) => Some(slot / MAX_CONNECTIONS),
+            LevelIssue::UnreachableJunction(junction)
+            | LevelIssue::UnreachablePellet(junction)
+            | LevelIssue::ReversingDeadEnd(junction)
+            | LevelIssue::IsolatedJunction(junction)
+            | LevelIssue::OverlappingJunctions(junction, _) => Some(*junction),
+            LevelIssue::MultiplePlayerSpawns(spawns) => spawns.first().copied(),
+            LevelIssue::MissingGhostHouse | LevelIssue::MissingPlayerSpawn => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LevelIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelIssue::AsymmetricLink(slot, target) => write!(
+                f,
+                "junction {} slot {} links to junction {} slot {}, which doesn't link back",
+                slot / MAX_CONNECTIONS,
+                slot % MAX_CONNECTIONS,
+                target / MAX_CONNECTIONS,
+                target % MAX_CONNECTIONS
+            ),
+            LevelIssue::DanglingIndex(slot, target) => write!(
+                f,
+                "junction {} slot {} links to slot {target}, which doesn't exist",
+                slot / MAX_CONNECTIONS,
+                slot % MAX_CONNECTIONS
+            ),
+            LevelIssue::UnreachableJunction(junction) => {
+                write!(
+                    f,
+                    "junction {junction} can't be reached from the player spawn"
+                )
+            }
+            LevelIssue::UnreachablePellet(junction) => {
+                write!(
+                    f,
+                    "the player can't get to the pellet at junction {junction}"
+                )
+            }
+            LevelIssue::MissingGhostHouse => write!(f, "there's no ghost house"),
+            LevelIssue::MissingPlayerSpawn => write!(f, "there's no player spawn"),
+            LevelIssue::MultiplePlayerSpawns(spawns) => {
+                write!(f, "there's more than one player spawn: {spawns:?}")
+            }
+            LevelIssue::ReversingDeadEnd(junction) => {
+                write!(
+                    f,
+                    "junction {junction} is a dead end, ghosts have to reverse out"
+                )
+            }
+            LevelIssue::IsolatedJunction(junction) => {
+                write!(f, "junction {junction} isn't linked to anything")
+            }
+            LevelIssue::OverlappingJunctions(junction, other) => {
+                write!(
+                    f,
+                    "junctions {junction} and {other} are on top of each other"
+                )
+            }
+            LevelIssue::CrossingLinks(slot, other_slot) => write!(
+                f,
+                "the links from junction {} and junction {} cross",
+                slot / MAX_CONNECTIONS,
+                other_slot / MAX_CONNECTIONS
+            ),
+        }
+    }
+}
+
+// Checking over a level and listing out everything wrong with it.
+pub fn validate_level(
+    junctions: &components::Junctions,
+    connections: &components::Connections,
+) -> Vec<LevelIssue> {
+    validate_level_from(junctions, connections, None)
+}
+
+// The same, but with the player starting somewhere other than the spawn. (None for the spawn)
+pub fn validate_level_from(
+    junctions: &components::Junctions,
+    connections: &components::Connections,
+    start: Option<usize>,
+) -> Vec<LevelIssue> {
+    let start = start.filter(|start| *start < junctions.0.len());
+    let mut issues = Vec::new();
+
+    // Every link should point somewhere real, and that somewhere should point back.
+    for (slot, connection) in connections.0.iter().enumerate() {
+        if let Some(target) = *connection {
+            if target >= connections.0.len() {
+                issues.push(LevelIssue::DanglingIndex(slot, target));
+            } else if connections.0[target] != Some(slot) {
+                issues.push(LevelIssue::AsymmetricLink(slot, target));
+            }
+        }
+    }
+
+    // Gotta have somewhere for everybody to start.
+    if !junctions
+        .0
+        .iter()
+        .any(|junction| junction.1 == JunctionType::GhostHouse)
+    {
+        issues.push(LevelIssue::MissingGhostHouse);
+    }
+    let spawns: Vec<usize> = junctions
+        .0
+        .iter()
+        .enumerate()
+        .filter(|(_, junction)| junction.1 == JunctionType::BonusItem)
+        .map(|(index, _)| index)
+        .collect();
+    match spawns.len() {
+        0 => issues.push(LevelIssue::MissingPlayerSpawn),
+        1 => {}
+        _ => issues.push(LevelIssue::MultiplePlayerSpawns(spawns.clone())),
+    }
+
+    // Walking the level out from the start (or the spawn, or just the first junction) to find any islands.
+    let mut unreachable = vec![false; junctions.0.len()];
+    if let Some(start) = start
+        .or(spawns.first().copied())
+        .or((!junctions.0.is_empty()).then_some(0))
+    {
+        let visited = reachable(junctions, connections, start, components::AgentMask::ALL);
+        // Junctions with no links at all get their own issue below.
+        for (junction, _) in visited.iter().enumerate().filter(|(_, seen)| !**seen) {
+            if !is_isolated(connections, junction) {
+                issues.push(LevelIssue::UnreachableJunction(junction));
+                unreachable[junction] = true;
+            }
+        }
+    }
+
+    // And again, just the way the player's allowed to go. Every pellet has to be eaten to clear the level.
+    if let Some(start) = start.or(spawns.first().copied()) {
+        let visited = reachable(junctions, connections, start, components::AgentMask::PLAYER);
+        for (junction, _) in junctions.0.iter().enumerate().filter(|(index, junction)| {
+            has_pellet(junction) && !visited[*index] && !unreachable[*index]
+        }) {
+            issues.push(LevelIssue::UnreachablePellet(junction));
+        }
+    }
+
+    // Junctions nobody bothered linking up.
+    for junction in 0..junctions.0.len() {
+        if is_isolated(connections, junction) {
+            issues.push(LevelIssue::IsolatedJunction(junction));
+        }
+    }
+
+    // Junctions stacked up on each other.
+    for (index, junction) in junctions.0.iter().enumerate() {
+        for (other, other_junction) in junctions.0.iter().enumerate().skip(index + 1) {
+            if junction.0.distance(other_junction.0) < EDITOR_JUNCTION_RADIUS {
+                issues.push(LevelIssue::OverlappingJunctions(index, other));
+            }
+        }
+    }
+
+    // Corridors crossing over each other. Tunnels go off the edge, so they don't count.
+    let corridors: Vec<(usize, usize)> = connections
+        .0
+        .iter()
+        .enumerate()
+        .filter_map(|(slot, target)| Some((slot, (*target)?)))
+        .filter(|(slot, target)| {
+            slot < target
+                && *target < connections.0.len()
+                && connections.1.get(*slot).map(|passage| passage.kind)
+                    != Some(components::PassageKind::Tunnel)
+        })
+        .collect();
+    for (index, (slot, target)) in corridors.iter().enumerate() {
+        for (other_slot, other_target) in corridors.iter().skip(index + 1) {
+            if crossing_point(junctions, (*slot, *target), (*other_slot, *other_target)).is_some() {
+                issues.push(LevelIssue::CrossingLinks(*slot, *other_slot));
+            }
+        }
+    }
+
+    // Dead ends. The ghost house gets a pass, that's where ghosts are meant to turn around.
+    for (index, junction) in junctions.0.iter().enumerate() {
+        if junction.1 != JunctionType::GhostHouse
+            && linked_junctions(junctions, connections, index, components::AgentMask::ALL).len()
+                == 1
+        {
+            issues.push(LevelIssue::ReversingDeadEnd(index));
+        }
+    }
+
+    issues
+}
+
+// Just the issues that stop a level from being played, from wherever the player starts. (None for the spawn)
+pub fn play_blockers(
+    junctions: &components::Junctions,
+    connections: &components::Connections,
+    start: Option<usize>,
+) -> Vec<LevelIssue> {
+    validate_level_from(junctions, connections, start)
+        .into_iter()
+        .filter(|issue| issue.is_error())
+        .collect()
+}
+
+// Whether a level's fit to play. Anything that gets a level into play should ask this first.
+// Complains about everything in the way if it isn't.
+pub fn can_play(junctions: &components::Junctions, connections: &components::Connections) -> bool {
+    let blockers = play_blockers(junctions, connections, None);
+    for blocker in &blockers {
+        warn!("Can't play this level: {blocker}");
+    }
+    blockers.is_empty()
+}
+
+// Where two links cross, if they do. Links that share a junction are just meeting up, not crossing.
+pub fn crossing_point(
+    junctions: &components::Junctions,
+    (slot, target): (usize, usize),
+    (other_slot, other_target): (usize, usize),
+) -> Option<Vec2> {
+    let ends = [slot, target, other_slot, other_target].map(|slot| slot / MAX_CONNECTIONS);
+    if ends[..2].iter().any(|end| ends[2..].contains(end)) {
+        return None;
+    }
+    let [start, end, other_start, other_end] =
+        ends.map(|junction| junctions.0.get(junction).map(|junction| junction.0));
+    let (start, end, other_start, other_end) = (start?, end?, other_start?, other_end?);
+
+    let direction = end - start;
+    let other_direction = other_end - other_start;
+    let denominator = direction.perp_dot(other_direction);
+    if denominator.abs() < f32::EPSILON {
+        return None; // Parallel, so they never properly cross.
+    }
+    let along = (other_start - start).perp_dot(other_direction) / denominator;
+    let other_along = (other_start - start).perp_dot(direction) / denominator;
+    ((0.0..=1.0).contains(&along) && (0.0..=1.0).contains(&other_along))
+        .then_some(start + direction * along)
+}
+
+// Whether the player has to eat something here to clear the level.
+pub fn has_pellet(junction: &components::Junction) -> bool {
+    matches!(junction.1, JunctionType::None | JunctionType::PowerPellet)
+}
+
+// Every junction these agents can get to from the start, following only the slots they're let out of.
+pub fn reachable(
+    junctions: &components::Junctions,
+    connections: &components::Connections,
+    start: usize,
+    mask: components::AgentMask,
+) -> Vec<bool> {
+    let mut visited = vec![false; junctions.0.len()];
+    if start >= junctions.0.len() {
+        return visited;
+    }
+    let mut to_visit = vec![start];
+    visited[start] = true;
+    while let Some(junction) = to_visit.pop() {
+        for neighbor in linked_junctions(junctions, connections, junction, mask) {
+            if !visited[neighbor] {
+                visited[neighbor] = true;
+                to_visit.push(neighbor);
+            }
+        }
+    }
+    visited
+}
+
+// Whether a junction hasn't got a single link.
+fn is_isolated(connections: &components::Connections, junction: usize) -> bool {
+    connections
+        .0
+        .get(junction * MAX_CONNECTIONS..(junction + 1) * MAX_CONNECTIONS)
+        .is_some_and(|slots| slots.iter().all(|slot| slot.is_none()))
+}
+
+// The junctions this one leads to, skipping over anything broken and anything none of these agents are allowed through.
+fn linked_junctions(
+    junctions: &components::Junctions,
+    connections: &components::Connections,
+    junction: usize,
+    mask: components::AgentMask,
+) -> Vec<usize> {
+    (0..MAX_CONNECTIONS)
+        .map(|slot| junction * MAX_CONNECTIONS + slot)
+        .filter(|slot| {
+            connections
+                .1
+                .get(*slot)
+                .is_none_or(|passage| passage.allowed.0 & mask.0 != 0)
+        })
+        .filter_map(|slot| connections.0.get(slot).copied().flatten())
+        .map(|target| target / MAX_CONNECTIONS)
+        .filter(|target| *target < junctions.0.len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::level::graph;
+
+    // A little loop with a ghost house and a player spawn. Plays just fine.
+    fn playable_loop() -> (components::Junctions, components::Connections) {
+        let mut junctions = components::Junctions(Vec::new());
+        let mut connections = components::Connections::default();
+        for (position, junction_type) in [
+            (Vec2::new(0.0, 0.0), JunctionType::BonusItem),
+            (Vec2::new(200.0, 0.0), JunctionType::None),
+            (Vec2::new(200.0, 200.0), JunctionType::GhostHouse),
+            (Vec2::new(0.0, 200.0), JunctionType::PowerPellet),
+        ] {
+            graph::add_junction(&mut junctions, &mut connections, position, junction_type);
+        }
+        for (first, second) in [(0, 1), (1, 2), (2, 3), (3, 0)] {
+            graph::link_junctions(&junctions, &mut connections, first, second);
+        }
+        (junctions, connections)
+    }
+
+    #[test]
+    fn playable_level_has_no_issues() {
+        let (junctions, connections) = playable_loop();
+        assert_eq!(validate_level(&junctions, &connections), Vec::new());
+        assert!(can_play(&junctions, &connections));
+    }
+
+    #[test]
+    fn missing_spawn() {
+        let (mut junctions, connections) = playable_loop();
+        junctions.0[0].1 = JunctionType::None;
+        assert!(validate_level(&junctions, &connections).contains(&LevelIssue::MissingPlayerSpawn));
+        assert!(!can_play(&junctions, &connections));
+    }
+
+    #[test]
+    fn multiple_spawns() {
+        let (mut junctions, connections) = playable_loop();
+        junctions.0[1].1 = JunctionType::BonusItem;
+        assert!(validate_level(&junctions, &connections)
+            .contains(&LevelIssue::MultiplePlayerSpawns(vec![0, 1])));
+        assert!(!can_play(&junctions, &connections));
+    }
+
+    #[test]
+    fn missing_ghost_house() {
+        let (mut junctions, connections) = playable_loop();
+        junctions.0[2].1 = JunctionType::None;
+        assert!(validate_level(&junctions, &connections).contains(&LevelIssue::MissingGhostHouse));
+        assert!(!can_play(&junctions, &connections));
+    }
+
+    #[test]
+    fn unreachable_junctions() {
+        let (mut junctions, mut connections) = playable_loop();
+        let first = graph::add_junction(
+            &mut junctions,
+            &mut connections,
+            Vec2::new(600.0, 0.0),
+            JunctionType::None,
+        );
+        let second = graph::add_junction(
+            &mut junctions,
+            &mut connections,
+            Vec2::new(800.0, 0.0),
+            JunctionType::None,
+        );
+        graph::link_junctions(&junctions, &mut connections, first, second);
+
+        let issues = validate_level(&junctions, &connections);
+        assert!(issues.contains(&LevelIssue::UnreachableJunction(first)));
+        assert!(issues.contains(&LevelIssue::UnreachableJunction(second)));
+        assert!(!can_play(&junctions, &connections));
+    }
+
+    #[test]
+    fn reachable_from_the_chosen_start() {
+        // An island with its own start, and the rest of the level cut off from it.
+        let (mut junctions, mut connections) = playable_loop();
+        let first = graph::add_junction(
+            &mut junctions,
+            &mut connections,
+            Vec2::new(600.0, 0.0),
+            JunctionType::None,
+        );
+        let second = graph::add_junction(
+            &mut junctions,
+            &mut connections,
+            Vec2::new(800.0, 0.0),
+            JunctionType::None,
+        );
+        graph::link_junctions(&junctions, &mut connections, first, second);
+
+        let issues = validate_level_from(&junctions, &connections, Some(first));
+        assert!(!issues.contains(&LevelIssue::UnreachableJunction(second)));
+        assert!(issues.contains(&LevelIssue::UnreachableJunction(0)));
+    }
+
+    #[test]
+    fn one_sided_links() {
+        let (junctions, mut connections) = playable_loop();
+        let slot = connections.0.iter().position(Option::is_some).unwrap();
+        let target = connections.0[slot].unwrap();
+        connections.0[target] = None;
+
+        assert!(validate_level(&junctions, &connections)
+            .contains(&LevelIssue::AsymmetricLink(slot, target)));
+        assert!(!can_play(&junctions, &connections));
+    }
+
+    #[test]
+    fn dangling_links() {
+        let (junctions, mut connections) = playable_loop();
+        let slot = connections.0.iter().position(Option::is_none).unwrap();
+        connections.0[slot] = Some(connections.0.len() + 3);
+
+        assert!(validate_level(&junctions, &connections)
+            .contains(&LevelIssue::DanglingIndex(slot, connections.0.len() + 3)));
+    }
+
+    #[test]
+    fn warnings_dont_block_play() {
+        let (mut junctions, mut connections) = playable_loop();
+        let lonely = graph::add_junction(
+            &mut junctions,
+            &mut connections,
+            Vec2::new(-400.0, 0.0),
+            JunctionType::GhostHouse,
+        );
+        assert!(validate_level(&junctions, &connections)
+            .contains(&LevelIssue::IsolatedJunction(lonely)));
+        assert!(can_play(&junctions, &connections));
+    }
+
+    #[test]
+    fn isolated_pellets_block_play() {
+        let (mut junctions, mut connections) = playable_loop();
+        let lonely = graph::add_junction(
+            &mut junctions,
+            &mut connections,
+            Vec2::new(-400.0, 0.0),
+            JunctionType::None,
+        );
+        let issues = validate_level(&junctions, &connections);
+        assert!(issues.contains(&LevelIssue::IsolatedJunction(lonely)));
+        assert!(issues.contains(&LevelIssue::UnreachablePellet(lonely)));
+        assert!(!can_play(&junctions, &connections));
+    }
+
+    #[test]
+    fn pellets_behind_ghost_doors_block_play() {
+        // A pellet round the back of the ghost house, through a door only the ghosts get through.
+        let (mut junctions, mut connections) = playable_loop();
+        let pellet = graph::add_junction(
+            &mut junctions,
+            &mut connections,
+            Vec2::new(400.0, 200.0),
+            JunctionType::None,
+        );
+        let (door, _) = graph::link_junctions(&junctions, &mut connections, 2, pellet).unwrap();
+        connections.1[door].allowed = components::AgentMask::GHOSTS;
+
+        let issues = validate_level(&junctions, &connections);
+        assert!(issues.contains(&LevelIssue::UnreachablePellet(pellet)));
+        assert!(!issues.contains(&LevelIssue::UnreachableJunction(pellet)));
+        assert!(!can_play(&junctions, &connections));
+
+        // Letting the player through sorts it out.
+        connections.1[door].allowed = components::AgentMask::ALL;
+        assert!(!validate_level(&junctions, &connections)
+            .contains(&LevelIssue::UnreachablePellet(pellet)));
+    }
+}
@@ -1,23 +1,36 @@
-// use crate::components::DestroyWhenNotThisState;
+use crate::components::DestroyWhenNotThisState;
 use crate::core::editor::EditorPlugin;
 use crate::core::game_state::GameStatePlugin;
+use crate::core::inspector::InspectorPlugin;
 use crate::core::level::LevelPlugin;
 use crate::core::objects::ObjectPlugin;
-// use crate::core::prelude::*;
+use crate::core::prelude::*;
+use crate::core::ui::UiPlugin;
 use bevy::prelude::*;
 pub(super) struct CorePlugin; // The base of the game.
 
 pub mod constants; // Cooonstants~
 pub mod editor; // An eye on the editooor.
 pub mod game_state; // An eye on our states.
+pub mod inspector; // Poking around under the hood.
+pub mod launch; // Command line bits and bobs.
 pub mod level; // For levely stuff.
 pub mod objects;
 pub mod prelude; // Yeah prelude! // Yeah.
+pub mod ui; // Words on the screen.
 
 impl Plugin for CorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((GameStatePlugin, EditorPlugin, LevelPlugin, ObjectPlugin));
-        app.add_systems(Startup, setup);
+        app.add_plugins((
+            GameStatePlugin,
+            EditorPlugin,
+            InspectorPlugin,
+            LevelPlugin,
+            ObjectPlugin,
+            UiPlugin,
+        ));
+        app.add_systems(Startup, setup)
+            .add_systems(Update, clean_components);
     }
 }
 
@@ -25,16 +38,16 @@ pub fn setup(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
 }
 
-// pub fn clean_components(
-//     mut commands: Commands,
-//     discarded_items: Query<(Entity, &DestroyWhenNotThisState)>,
-//     current_game_state: Res<State<GameState>>,
-// ) {
-//     // For each entity that isn't in the current Game State.
-//     for entity in discarded_items
-//         .iter()
-//         .filter(|item| &item.1 .0 != current_game_state.get())
-//     {
-//         commands.entity(entity.0).despawn(); // Getting rid of that thang.
-//     }
-// }
+pub fn clean_components(
+    mut commands: Commands,
+    discarded_items: Query<(Entity, &DestroyWhenNotThisState)>,
+    current_game_state: Res<State<GameState>>,
+) {
+    // For each entity that isn't in the current Game State.
+    for entity in discarded_items
+        .iter()
+        .filter(|item| &item.1 .0 != current_game_state.get())
+    {
+        commands.entity(entity.0).despawn_recursive(); // Getting rid of that thang, and everything hanging off it.
+    }
+}
@@ -1,4 +1,5 @@
 use crate::components::*;
+use crate::core::level::{graph, hot_reload::reload_level, validation, LevelReplaced};
 use crate::core::prelude::*;
 use bevy::prelude::*;
 
@@ -6,80 +7,177 @@ pub(super) struct AgentPlugin;
 
 impl Plugin for AgentPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing(Playing::New)), spawn_agents)
+        app.init_resource::<Frightened>()
+            .init_resource::<PlayerSpawn>()
+            .init_resource::<EatenPellets>()
+            .add_systems(
+                OnEnter(GameState::Playing(Playing::New)),
+                (spawn_agents, reset_frightened, reset_pellets),
+            )
             .add_systems(OnExit(GameState::Playing(Playing::Running)), remove_agents)
+            // Nobody should be wandering around the editor, however we got back to it.
+            .add_systems(
+                Update,
+                remove_agents.run_if(in_editor.and_then(any_with_component::<NavAgent>)),
+            )
             .add_systems(
                 FixedUpdate,
-                (move_agents).run_if(in_state(GameState::Playing(Playing::Running))),
+                (move_agents, frighten_ghosts, eat_pellets)
+                    .chain()
+                    .run_if(in_state(GameState::Playing(Playing::Running))),
+            )
+            // After the reload, so nobody's left holding onto junctions that aren't there anymore.
+            .add_systems(
+                Update,
+                respawn_agents.after(reload_level).run_if(in_playing),
             );
     }
 }
 
+// Keeping track of the power pellets.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct Frightened {
+    pub timer: Option<Timer>, // How much longer the ghosts are scared for. (None if they aren't)
+    pub eaten: Vec<usize>,    // The power pellet junctions that have already been used up.
+}
+
+#[derive(Resource, Default)]
+pub struct EatenPellets(pub Vec<usize>); // Every junction the player's cleared the pellet off this level.
+
+#[derive(Resource, Default)]
+pub struct PlayerSpawn(pub Option<usize>); // Where the player starts instead of the bonus item, for test playing. (None for the usual spot)
+
 // Spawn in an agent.
 fn spawn_agents(
     mut commands: Commands,
     junctions: Res<Junctions>,
+    level_info: Res<LevelInfo>,
+    player_spawn: Res<PlayerSpawn>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
+    spawn_level_agents(
+        &mut commands,
+        &junctions,
+        &level_info,
+        player_spawn.0,
+        &mut meshes,
+        &mut materials,
+    );
+}
+
+// The level changed mid-game. Clear everyone out and start them over on the new one.
+#[allow(clippy::too_many_arguments)] // Systems gonna system.
+fn respawn_agents(
+    mut commands: Commands,
+    mut level_replaced: EventReader<LevelReplaced>,
+    discarded: Query<Entity, With<NavAgent>>,
+    junctions: Res<Junctions>,
+    level_info: Res<LevelInfo>,
+    player_spawn: Res<PlayerSpawn>,
+    mut frightened: ResMut<Frightened>,
+    mut eaten_pellets: ResMut<EatenPellets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if level_replaced.read().count() == 0 {
+        return;
+    }
+
+    // The old junction indices don't mean anything anymore.
+    *frightened = Frightened::default();
+    eaten_pellets.0.clear();
+
+    for discarded_entity in discarded.iter() {
+        commands.entity(discarded_entity).despawn(); // Byebye.
+    }
+    spawn_level_agents(
+        &mut commands,
+        &junctions,
+        &level_info,
+        player_spawn.0,
+        &mut meshes,
+        &mut materials,
+    );
+}
+
+// Putting an agent on every junction that wants one.
+fn spawn_level_agents(
+    commands: &mut Commands,
+    junctions: &Junctions,
+    level_info: &LevelInfo,
+    player_spawn: Option<usize>,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) {
+    let roster = level_info
+        .rules
+        .ghost_roster
+        .as_deref()
+        .unwrap_or(&DEFAULT_GHOST_ROSTER);
+
     for (index, junction) in junctions.0.iter().enumerate() {
         match junction.1 {
             JunctionType::None => {}
             JunctionType::PowerPellet => {}
             JunctionType::GhostHouse => {
-                for iterator in 0..4 {
+                for ghost_type in roster {
                     commands.spawn((
                         bevy::sprite::MaterialMesh2dBundle {
                             mesh: meshes.add(Circle::new(EDITOR_JUNCTION_RADIUS * 0.9)).into(),
-                            material: materials.add(Color::hsl(
-                                match iterator {
-                                    0 => 0.0,
-                                    1 => 294.0,
-                                    2 => 187.0,
-                                    3 => 35.0,
-                                    _ => 156.0,
-                                },
-                                1.0,
-                                0.5,
-                            )),
+                            material: materials.add(Color::hsl(agent_hue(*ghost_type), 1.0, 0.5)),
                             transform: Transform::from_xyz(junction.0.x, junction.0.y, 0.0),
                             ..default()
                         },
                         NavAgent {
                             junction_target: Some(index),
                             junction_pool: Vec::new(),
-                            type_base: match iterator {
-                                0 => NavAgentType::Shadow,
-                                1 => NavAgentType::Sneaky,
-                                2 => NavAgentType::Moody,
-                                3 => NavAgentType::Pokey,
-                                _ => NavAgentType::Fearful,
-                            },
+                            type_base: *ghost_type,
                             type_modified: None,
+                            tunnel: None,
                         },
                     ));
                 }
             }
-            JunctionType::BonusItem => {
-                commands.spawn((
-                    bevy::sprite::MaterialMesh2dBundle {
-                        mesh: meshes.add(Circle::new(EDITOR_JUNCTION_RADIUS * 0.9)).into(),
-                        material: materials.add(Color::hsl(57.0, 1.0, 0.5)),
-                        transform: Transform::from_xyz(junction.0.x, junction.0.y, 0.0),
-                        ..default()
-                    },
-                    NavAgent {
-                        junction_target: Some(index),
-                        junction_pool: Vec::new(),
-                        type_base: NavAgentType::Player,
-                        type_modified: None,
-                    },
-                ));
-            }
+            JunctionType::BonusItem => {}
+        }
+
+        // The player starts on the bonus item, unless they've been told to start somewhere else.
+        if player_spawn.map_or(junction.1 == JunctionType::BonusItem, |spawn| {
+            spawn == index
+        }) {
+            commands.spawn((
+                bevy::sprite::MaterialMesh2dBundle {
+                    mesh: meshes.add(Circle::new(EDITOR_JUNCTION_RADIUS * 0.9)).into(),
+                    material: materials.add(Color::hsl(agent_hue(NavAgentType::Player), 1.0, 0.5)),
+                    transform: Transform::from_xyz(junction.0.x, junction.0.y, 0.0),
+                    ..default()
+                },
+                NavAgent {
+                    junction_target: Some(index),
+                    junction_pool: Vec::new(),
+                    type_base: NavAgentType::Player,
+                    type_modified: None,
+                    tunnel: None,
+                },
+            ));
         }
     }
 }
 
+// Everyone gets their own color.
+fn agent_hue(agent_type: NavAgentType) -> f32 {
+    match agent_type {
+        NavAgentType::Player => 57.0,
+        NavAgentType::Shadow => 0.0,
+        NavAgentType::Sneaky => 294.0,
+        NavAgentType::Moody => 187.0,
+        NavAgentType::Pokey => 35.0,
+        NavAgentType::Fearful => 156.0,
+    }
+}
+
 // Get rid of them.
 fn remove_agents(mut commands: Commands, discarded: Query<Entity, With<NavAgent>>) {
     for discarded_entity in discarded.iter() {
@@ -94,30 +192,90 @@ fn move_agents(
     mut agents: Query<(&mut Transform, &mut NavAgent)>,
     junctions: Res<Junctions>,
     connections: Res<Connections>,
+    level_info: Res<LevelInfo>,
 ) {
+    let speed = level_info.rules.agent_speed.unwrap_or(DEFAULT_AGENT_SPEED);
+    let ghost_tunnel_speed = level_info
+        .rules
+        .ghost_tunnel_speed
+        .unwrap_or(DEFAULT_GHOST_TUNNEL_SPEED);
     for (mut transform, mut nav_agent) in &mut agents {
+        // How fast this one's going right now.
+        let agent_speed = speed
+            * match nav_agent.type_modified {
+                Some(NavAgentType::Fearful) => 0.5, // Scared stiff.
+                _ => 1.0,
+            }
+            * match nav_agent.tunnel {
+                Some(_) if AgentMask::GHOSTS.allows(nav_agent.type_base) => ghost_tunnel_speed, // Tight squeeze.
+                _ => 1.0,
+            };
+
+        // Halfway through a tunnel, walking out the mouth. Once we're there, pop out the other end.
+        if let Some(TunnelTrip::Leaving(mouth, other_mouth)) = nav_agent.tunnel {
+            transform.translation = transform
+                .translation
+                .move_towards(mouth.extend(0.0), time.delta_seconds() * agent_speed);
+            if transform.translation.truncate().distance(mouth) < 0.01 {
+                transform.translation = other_mouth.extend(0.0);
+                nav_agent.tunnel = Some(TunnelTrip::Arriving);
+            }
+            continue;
+        }
+
         if let Some(valid_position) = nav_agent.junction_target {
-            // We have a valid position to go to.
+            // We have a valid position to go to. Unless the level's changed out from under us.
+            let Some(target_position) = junctions.0.get(valid_position).map(|junction| junction.0)
+            else {
+                nav_agent.junction_target = None;
+                continue;
+            };
 
             // If we're close to a node.
-            if (junctions.0[valid_position].0.x - transform.translation.x).powf(2.0)
-                + (junctions.0[valid_position].0.y - transform.translation.y).powf(2.0)
+            if (target_position.x - transform.translation.x).powf(2.0)
+                + (target_position.y - transform.translation.y).powf(2.0)
                 < (2.0_f32).powf(5.0)
             {
                 // Resetting the pool.
                 nav_agent.junction_pool = Vec::new();
+                nav_agent.tunnel = None; // Made it through, if we were in one.
 
                 // Making a new set of connections.
                 for possible_target in 0..MAX_CONNECTIONS {
-                    // If there is a valid target, add it to our target pool.
-                    if let Some(found_target) =
-                        connections.0[valid_position * MAX_CONNECTIONS + possible_target]
+                    let slot = valid_position * MAX_CONNECTIONS + possible_target;
+                    // If there is a valid target we're allowed through to, add it to our target pool.
+                    if let Some(found_target) = connections
+                        .0
+                        .get(slot)
+                        .copied()
+                        .flatten()
+                        .filter(|found_target| found_target / MAX_CONNECTIONS < junctions.0.len())
+                        .filter(|_| connections.1[slot].allowed.allows(nav_agent.type_base))
                     {
                         nav_agent.junction_pool.push(found_target / MAX_CONNECTIONS);
                         // Converting it back into a junction.
                     }
                 }
 
+                // The slot we'd leave through to get to a junction.
+                let type_base = nav_agent.type_base;
+                let exit_slot = |target: usize| {
+                    (0..MAX_CONNECTIONS)
+                        .map(|slot| valid_position * MAX_CONNECTIONS + slot)
+                        .find(|slot| {
+                            connections.0[*slot]
+                                .is_some_and(|other_slot| other_slot / MAX_CONNECTIONS == target)
+                                && connections.1[*slot].allowed.allows(type_base)
+                        })
+                };
+                // Where heading for a junction actually takes us. Tunnels go out the side, not across the level.
+                let heading = |target: usize| match exit_slot(target) {
+                    Some(slot) if connections.1[slot].kind == PassageKind::Tunnel => {
+                        target_position + graph::slot_direction(slot) * TUNNEL_MOUTH_DEPTH
+                    }
+                    _ => junctions.0[target].0,
+                };
+
                 // If we're not the player, we can't go backwards... As long as there's options.
                 if nav_agent.type_base != NavAgentType::Player && nav_agent.junction_pool.len() > 1
                 {
@@ -145,24 +303,21 @@ fn move_agents(
                         if direction.distance(Vec3::ZERO).abs() < 0.25 {
                             nav_agent.junction_target // Just stay put if there's no input.
                         } else {
-                            match nav_agent.junction_pool.iter().min_by(|x, y| {
-                                target_transform
-                                    .distance(Vec3::new(
-                                        junctions.0[**x].0.x,
-                                        junctions.0[**x].0.x,
-                                        0.0,
-                                    ))
-                                    .total_cmp(&target_transform.distance(Vec3::new(
-                                        junctions.0[**y].0.x,
-                                        junctions.0[**y].0.x,
-                                        0.0,
-                                    )))
-                            }) {
-                                Some(value) => Some(*value),
-                                None => None,
-                            }
+                            nav_agent
+                                .junction_pool
+                                .iter()
+                                .min_by(|x, y| {
+                                    target_transform
+                                        .distance(heading(**x).extend(0.0))
+                                        .total_cmp(
+                                            &target_transform.distance(heading(**y).extend(0.0)),
+                                        )
+                                })
+                                .copied()
                         }
                     }
+                    // Nowhere we're allowed to go, so just wait here.
+                    _ if nav_agent.junction_pool.is_empty() => nav_agent.junction_target,
                     _ => Some(
                         nav_agent.junction_pool
                             [rand::random::<usize>() % nav_agent.junction_pool.len()],
@@ -191,17 +346,153 @@ fn move_agents(
                     // NavAgentType::Pokey => todo!(),
                     // NavAgentType::Fearful => todo!(),
                 };
+
+                // Heading into a tunnel? Off out the mouth first, then.
+                if let Some(target) = nav_agent
+                    .junction_target
+                    .filter(|target| *target != valid_position)
+                {
+                    if let Some(slot) = exit_slot(target)
+                        .filter(|slot| connections.1[*slot].kind == PassageKind::Tunnel)
+                    {
+                        if let Some(other_slot) = connections.0[slot] {
+                            nav_agent.tunnel = Some(TunnelTrip::Leaving(
+                                heading(target),
+                                junctions.0[target].0
+                                    + graph::slot_direction(other_slot) * TUNNEL_MOUTH_DEPTH,
+                            ));
+                        }
+                    }
+                }
             } else {
                 // Just keep swimming.
                 transform.translation = transform.translation.move_towards(
-                    Vec3::new(
-                        junctions.0[valid_position].0.x,
-                        junctions.0[valid_position].0.y,
-                        0.0,
-                    ),
-                    time.delta_seconds() * 250.0,
+                    Vec3::new(target_position.x, target_position.y, 0.0),
+                    time.delta_seconds() * agent_speed,
                 );
             }
         }
     }
 }
+
+// Fresh level, fresh pellets.
+fn reset_frightened(mut frightened: ResMut<Frightened>) {
+    *frightened = Frightened::default();
+}
+
+// And the regular ones too.
+fn reset_pellets(mut eaten_pellets: ResMut<EatenPellets>) {
+    eaten_pellets.0.clear();
+}
+
+// The player munching through the pellets. Every last one they can get to gone and the level's beaten.
+fn eat_pellets(
+    agents: Query<(&Transform, &NavAgent)>,
+    junctions: Res<Junctions>,
+    connections: Res<Connections>,
+    player_spawn: Res<PlayerSpawn>,
+    mut eaten_pellets: ResMut<EatenPellets>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (transform, _) in agents
+        .iter()
+        .filter(|(_, nav_agent)| nav_agent.type_base == NavAgentType::Player)
+    {
+        if let Some(pellet) = junctions
+            .0
+            .iter()
+            .enumerate()
+            .position(|(index, junction)| {
+                validation::has_pellet(junction)
+                    && !eaten_pellets.0.contains(&index)
+                    && junction.0.distance(transform.translation.truncate())
+                        < EDITOR_JUNCTION_RADIUS
+            })
+        {
+            eaten_pellets.0.push(pellet);
+        }
+    }
+
+    // Pellets the player can't ever get to don't count, or the level could never be beaten.
+    let Some(start) = player_spawn.0.or_else(|| {
+        junctions
+            .0
+            .iter()
+            .position(|junction| junction.1 == JunctionType::BonusItem)
+    }) else {
+        return;
+    };
+    let reachable = validation::reachable(&junctions, &connections, start, AgentMask::PLAYER);
+    let pellets: Vec<usize> = junctions
+        .0
+        .iter()
+        .enumerate()
+        .filter(|(index, junction)| reachable[*index] && validation::has_pellet(junction))
+        .map(|(index, _)| index)
+        .collect();
+
+    // A level with no pellets at all has nothing to win.
+    if !pellets.is_empty()
+        && pellets
+            .iter()
+            .all(|pellet| eaten_pellets.0.contains(pellet))
+    {
+        info!("Level cleared!");
+        next_state.set(GameState::Playing(Playing::Complete));
+    }
+}
+
+// The player grabbing a power pellet sends the ghosts running for a while.
+fn frighten_ghosts(
+    time: Res<Time>,
+    mut frightened: ResMut<Frightened>,
+    mut agents: Query<(&Transform, &mut NavAgent)>,
+    junctions: Res<Junctions>,
+    level_info: Res<LevelInfo>,
+) {
+    // Checking if the player's sitting on a pellet nobody's eaten yet.
+    let pellet = agents
+        .iter()
+        .filter(|(_, nav_agent)| nav_agent.type_base == NavAgentType::Player)
+        .find_map(|(transform, _)| {
+            junctions
+                .0
+                .iter()
+                .enumerate()
+                .position(|(index, junction)| {
+                    junction.1 == JunctionType::PowerPellet
+                        && !frightened.eaten.contains(&index)
+                        && junction.0.distance(transform.translation.truncate())
+                            < EDITOR_JUNCTION_RADIUS
+                })
+        });
+
+    if let Some(pellet) = pellet {
+        frightened.eaten.push(pellet);
+        frightened.timer = Some(Timer::from_seconds(
+            level_info
+                .rules
+                .frightened_duration
+                .unwrap_or(DEFAULT_FRIGHTENED_DURATION),
+            TimerMode::Once,
+        ));
+        for (_, mut nav_agent) in &mut agents {
+            if nav_agent.type_base != NavAgentType::Player {
+                nav_agent.type_modified = Some(NavAgentType::Fearful);
+            }
+        }
+    }
+
+    // And calming them back down once it wears off.
+    let Some(timer) = &mut frightened.timer else {
+        return;
+    };
+    if timer.tick(time.delta()).finished() {
+        frightened.timer = None;
+        for (_, mut nav_agent) in &mut agents {
+            if nav_agent.type_modified == Some(NavAgentType::Fearful) {
+                nav_agent.type_modified = None;
+            }
+        }
+    }
+}
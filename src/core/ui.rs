@@ -0,0 +1,563 @@
+use crate::components::{Connections, DestroyWhenNotThisState, JunctionType, Junctions, LevelInfo};
+use crate::core::editor::{
+    camera::EditorView, overlay::LevelIssues, palette::JunctionBrush, snapping::EditorSnapping,
+    symmetry::EditorSymmetry, HoveredJunction, MetadataEditor, MetadataField, NewLevelWizard,
+    PossibleConnection,
+};
+use crate::core::prelude::*;
+use bevy::{prelude::*, ui::UiSystem};
+
+pub(super) struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Menu(Menu::Victory)),
+            spawn_victory_screen,
+        )
+        .add_systems(
+            OnEnter(GameState::Editor(Editor::Metadata)),
+            spawn_metadata_panel,
+        )
+        .add_systems(
+            Update,
+            update_metadata_panel.run_if(in_state(GameState::Editor(Editor::Metadata))),
+        )
+        .add_systems(
+            Update,
+            (update_hud_level, update_hud_cursor, update_hud_tools).run_if(in_editor),
+        )
+        .add_systems(OnEnter(GameState::Editor(Editor::Nodes)), spawn_palette_bar)
+        .add_systems(
+            Update,
+            palette_bar.run_if(in_state(GameState::Editor(Editor::Nodes))),
+        )
+        .add_systems(
+            Update,
+            (update_issue_list, issue_list).run_if(
+                in_state(GameState::Editor(Editor::Nodes))
+                    .or_else(in_state(GameState::Editor(Editor::Connections))),
+            ),
+        )
+        .add_systems(PreUpdate, block_input_under_buttons.after(UiSystem::Focus));
+
+        // Every editor gets the HUD, with its own cheat sheet.
+        for editor in [
+            Editor::New,
+            Editor::Nodes,
+            Editor::Connections,
+            Editor::Metadata,
+        ] {
+            app.add_systems(OnEnter(GameState::Editor(editor)), spawn_editor_hud);
+        }
+        // Only the editors actually looking at the level get told what's wrong with it.
+        for editor in [Editor::Nodes, Editor::Connections] {
+            app.add_systems(OnEnter(GameState::Editor(editor)), spawn_issue_list);
+        }
+    }
+}
+
+#[derive(Component)]
+struct HudLevelText; // Which editor, and what's in the level.
+
+#[derive(Component)]
+struct HudCursorText; // What's under the cursor, and what's waiting to be linked.
+
+#[derive(Component)]
+struct HudToolsText; // Which helpers are switched on.
+
+#[derive(Component)]
+struct PaletteButton(JunctionType); // One brush on the palette bar.
+
+#[derive(Component)]
+struct IssueList; // The panel listing everything wrong with the level.
+
+#[derive(Component)]
+struct IssueButton(Option<usize>); // One problem on the list, and the junction to go look at.
+
+#[derive(Component)]
+struct MetadataText; // The text listing out the level info.
+
+// A big ol' congratulations.
+fn spawn_victory_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            DestroyWhenNotThisState(GameState::Menu(Menu::Victory)),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "YOU WIN!",
+                TextStyle {
+                    font_size: 96.0,
+                    color: bevy::color::palettes::css::YELLOW.into(),
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                "Every level's been cleared. Press 1 to head back to the editor.",
+                TextStyle {
+                    font_size: 24.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+// A list of everything that can be said about the level.
+fn spawn_metadata_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(16.0),
+                    top: Val::Px(16.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                ..default()
+            },
+            DestroyWhenNotThisState(GameState::Editor(Editor::Metadata)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                ),
+                MetadataText,
+            ));
+        });
+}
+
+// Keeping the list up to date with whatever's being typed.
+fn update_metadata_panel(
+    level_info: Res<LevelInfo>,
+    metadata_editor: Res<MetadataEditor>,
+    mut texts: Query<&mut Text, With<MetadataText>>,
+) {
+    let mut lines =
+        vec!["LEVEL INFO (Up/Down to pick, type to edit, blank for default)".to_string()];
+    for field in MetadataField::ALL {
+        lines.push(if field == metadata_editor.field {
+            format!(
+                "> {}: {}_{}",
+                field.label(),
+                metadata_editor.buffer,
+                if metadata_editor.valid { "" } else { "  (?)" } // Not something we can use.
+            )
+        } else {
+            format!("  {}: {}", field.label(), field.read(&level_info))
+        });
+    }
+
+    for mut text in &mut texts {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+// The hotkeys for each editor.
+fn cheat_sheet(editor: Editor) -> &'static str {
+    match editor {
+        Editor::New => {
+            "Left/Right: template\n\
+             Up/Down: rows, PgUp/PgDn: columns (grids, mazes)\n\
+             Home/End: more / fewer loops, F5: new seed, F6: mirror (mazes)\n\
+             = / -: grow / shrink\n\
+             Type: name it\n\
+             Enter or Tab: start editing"
+        }
+        Editor::Nodes => {
+            "LMB: place / drag / box select\n\
+             Shift+LMB: add to selection\n\
+             RMB or Delete: delete\n\
+             Alt+1, Alt+2...: pick a brush, MMB: paint with it\n\
+             Ctrl+C / V / D: copy / paste / duplicate\n\
+             A / Shift+A: auto-connect picked / all\n\
+             L / Shift+L: straighten picked / all\n\
+             G: grid, [ ]: grid size, H: guides\n\
+             M: mirror, Shift+M: move axis\n\
+             Wheel: zoom, MMB or Space drag: pan, F: frame\n\
+             Ctrl+Z / Ctrl+Shift+Z: undo / redo\n\
+             Ctrl+S: save, Ctrl+N: new, Ctrl+I / E: import / export\n\
+             F12: world inspector\n\
+             P: test play from the junction under the cursor\n\
+             Tab: connections"
+        }
+        Editor::Connections => {
+            "LMB slot, then LMB slot: link\n\
+             Drag junction to junction: link\n\
+             RMB junction: unlink everything\n\
+             R on a slot: who can leave through it\n\
+             O on a slot: one-way out of it\n\
+             T on a slot: tunnel or corridor\n\
+             A / Shift+A: auto-connect picked / all\n\
+             L / Shift+L: straighten picked / all\n\
+             M: mirror, Shift+M: move axis\n\
+             Wheel: zoom, MMB or Space drag: pan, F: frame\n\
+             Ctrl+Z / Ctrl+Shift+Z: undo / redo\n\
+             Ctrl+S: save, F12: world inspector\n\
+             P: test play from the junction under the cursor\n\
+             Tab: level info, 2: play"
+        }
+        Editor::Metadata => {
+            "Up/Down or Enter: pick a field\n\
+             Type: edit it, blank for the default\n\
+             Ctrl+Z / Ctrl+Shift+Z: undo / redo\n\
+             Tab: back to nodes"
+        }
+        Editor::Saving => "",
+    }
+}
+
+// Status down the bottom left, hotkeys up the top right.
+fn spawn_editor_hud(mut commands: Commands, state: Res<State<GameState>>) {
+    let GameState::Editor(editor) = *state.get() else {
+        return;
+    };
+    let text_style = TextStyle {
+        font_size: 16.0,
+        ..default()
+    };
+    let panel = |style: Style| NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(8.0)),
+            ..style
+        },
+        background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            panel(Style {
+                left: Val::Px(16.0),
+                bottom: Val::Px(16.0),
+                ..default()
+            }),
+            DestroyWhenNotThisState(*state.get()),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", text_style.clone()),
+                HudLevelText,
+            ));
+            parent.spawn((
+                TextBundle::from_section("", text_style.clone()),
+                HudCursorText,
+            ));
+            parent.spawn((
+                TextBundle::from_section("", text_style.clone()),
+                HudToolsText,
+            ));
+        });
+
+    commands
+        .spawn((
+            panel(Style {
+                right: Val::Px(16.0),
+                top: Val::Px(16.0),
+                ..default()
+            }),
+            DestroyWhenNotThisState(*state.get()),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                cheat_sheet(editor),
+                TextStyle {
+                    color: bevy::color::palettes::css::LIGHT_GRAY.into(),
+                    ..text_style
+                },
+            ));
+        });
+}
+
+// Which editor we're in, and how big the level is.
+fn update_hud_level(
+    state: Res<State<GameState>>,
+    level_info: Res<LevelInfo>,
+    junctions: Res<Junctions>,
+    connections: Res<Connections>,
+    wizard: Res<NewLevelWizard>,
+    mut texts: Query<&mut Text, With<HudLevelText>>,
+) {
+    let line = match state.get() {
+        GameState::Editor(Editor::New) => format!(
+            "NEW LEVEL: {} from {:?}, {}x{}",
+            if wizard.name.is_empty() {
+                "(unnamed)"
+            } else {
+                &wizard.name
+            },
+            wizard.template,
+            wizard.size.x.round(),
+            wizard.size.y.round()
+        ),
+        GameState::Editor(editor) => format!(
+            "{}: \"{}\", {} junctions, {} links",
+            format!("{editor:?}").to_uppercase(),
+            level_info.name,
+            junctions.0.len(),
+            connections.0.iter().flatten().count() / 2
+        ),
+        _ => String::new(),
+    };
+    for mut text in &mut texts {
+        text.sections[0].value.clone_from(&line);
+    }
+}
+
+// What the cursor's over, and any link that's halfway made.
+fn update_hud_cursor(
+    junctions: Res<Junctions>,
+    hovered_junction: Res<HoveredJunction>,
+    possible_connection: Res<PossibleConnection>,
+    mut texts: Query<&mut Text, With<HudCursorText>>,
+) {
+    let hovered = match hovered_junction
+        .0
+        .and_then(|index| Some((index, junctions.0.get(index)?)))
+    {
+        Some((index, junction)) => format!("Junction #{index} ({:?})", junction.1),
+        None => "Nothing under the cursor".to_string(),
+    };
+    let pending = match possible_connection.0 {
+        Some(slot) => format!(
+            ", linking from #{} slot {}",
+            slot / MAX_CONNECTIONS,
+            slot % MAX_CONNECTIONS
+        ),
+        None => String::new(),
+    };
+    for mut text in &mut texts {
+        text.sections[0].value = format!("{hovered}{pending}");
+    }
+}
+
+// Which helpers are on.
+fn update_hud_tools(
+    snapping: Res<EditorSnapping>,
+    symmetry: Res<EditorSymmetry>,
+    view: Res<EditorView>,
+    brush: Res<JunctionBrush>,
+    mut texts: Query<&mut Text, With<HudToolsText>>,
+) {
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let line = format!(
+        "Brush {:?}, grid {} ({}), guides {}, mirror {}, zoom {:.0}%",
+        brush.0,
+        on_off(snapping.grid),
+        snapping.grid_size,
+        on_off(snapping.guides),
+        match symmetry.axis {
+            Some(axis) => format!("{axis:?}"),
+            None => "off".to_string(),
+        },
+        100.0 / view.zoom
+    );
+    for mut text in &mut texts {
+        text.sections[0].value.clone_from(&line);
+    }
+}
+
+// Clicking a button shouldn't click whatever's behind it too.
+// Only presses that start on a button get swallowed. Drags that wander over one carry on like it isn't there.
+fn block_input_under_buttons(
+    interactions: Query<&Interaction, With<Button>>,
+    mut buttons: ResMut<ButtonInput<MouseButton>>,
+) {
+    if !interactions
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        return;
+    }
+    // Forgetting the press outright, so its release doesn't turn up anywhere either.
+    let pressed: Vec<MouseButton> = buttons.get_just_pressed().copied().collect();
+    for button in pressed {
+        buttons.reset(button);
+    }
+}
+
+// A brush for every junction type, along the top of the Nodes editor.
+fn spawn_palette_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(16.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            },
+            DestroyWhenNotThisState(GameState::Editor(Editor::Nodes)),
+        ))
+        .with_children(|parent| {
+            for (index, junction_type) in JunctionType::all().into_iter().enumerate() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+                                border: UiRect::all(Val::Px(2.0)),
+                                ..default()
+                            },
+                            background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                            ..default()
+                        },
+                        PaletteButton(junction_type),
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            format!("Alt+{} {junction_type:?}", index + 1),
+                            TextStyle {
+                                font_size: 16.0,
+                                color: junction_type.color().into(),
+                                ..default()
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+// Picking a brush off the bar, and showing which one's picked.
+fn palette_bar(
+    mut brush: ResMut<JunctionBrush>,
+    mut palette_buttons: Query<(&PaletteButton, &Interaction, &mut BorderColor)>,
+) {
+    for (palette_button, interaction, _) in &palette_buttons {
+        if *interaction == Interaction::Pressed && brush.0 != palette_button.0 {
+            brush.0 = palette_button.0;
+        }
+    }
+    for (palette_button, interaction, mut border_color) in &mut palette_buttons {
+        border_color.0 = if palette_button.0 == brush.0 {
+            Color::WHITE
+        } else if *interaction == Interaction::Hovered {
+            bevy::color::palettes::css::GRAY.into()
+        } else {
+            Color::NONE
+        };
+    }
+}
+
+// Problems down the bottom right, filled in by update_issue_list.
+fn spawn_issue_list(mut commands: Commands, state: Res<State<GameState>>) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(16.0),
+                bottom: Val::Px(16.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        },
+        IssueList,
+        DestroyWhenNotThisState(*state.get()),
+    ));
+}
+
+// Rebuilding the list whenever the problems change, or the panel's brand new.
+fn update_issue_list(
+    mut commands: Commands,
+    issues: Res<LevelIssues>,
+    lists: Query<Entity, With<IssueList>>,
+    new_lists: Query<(), Added<IssueList>>,
+) {
+    if !issues.is_changed() && new_lists.is_empty() {
+        return;
+    }
+    for list in &lists {
+        commands.entity(list).despawn_descendants();
+        commands.entity(list).with_children(|parent| {
+            for issue in issues.0.iter().take(EDITOR_ISSUE_LIST_LENGTH) {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                                border: UiRect::all(Val::Px(2.0)),
+                                ..default()
+                            },
+                            background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                            ..default()
+                        },
+                        IssueButton(issue.junction()),
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            issue.to_string(),
+                            TextStyle {
+                                font_size: 16.0,
+                                color: if issue.is_error() {
+                                    bevy::color::palettes::css::RED.into()
+                                } else {
+                                    bevy::color::palettes::css::ORANGE.into()
+                                },
+                                ..default()
+                            },
+                        ));
+                    });
+            }
+            if issues.0.len() > EDITOR_ISSUE_LIST_LENGTH {
+                parent.spawn(TextBundle::from_section(
+                    format!("...and {} more", issues.0.len() - EDITOR_ISSUE_LIST_LENGTH),
+                    TextStyle {
+                        font_size: 16.0,
+                        color: bevy::color::palettes::css::LIGHT_GRAY.into(),
+                        ..default()
+                    },
+                ));
+            }
+        });
+    }
+}
+
+// Clicking a problem swings the camera over to it.
+fn issue_list(
+    junctions: Res<Junctions>,
+    mut view: ResMut<EditorView>,
+    mut issue_buttons: Query<(&IssueButton, &Interaction, &mut BorderColor), Changed<Interaction>>,
+) {
+    for (issue_button, interaction, mut border_color) in &mut issue_buttons {
+        if *interaction == Interaction::Pressed {
+            if let Some(junction) = issue_button
+                .0
+                .and_then(|junction| junctions.0.get(junction))
+            {
+                view.position = junction.0;
+            }
+        }
+        border_color.0 = if *interaction == Interaction::None {
+            Color::NONE
+        } else {
+            bevy::color::palettes::css::GRAY.into()
+        };
+    }
+}
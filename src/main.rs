@@ -5,6 +5,13 @@ mod core;
 
 // The meat, the bones, the core of the program.
 fn main() {
+    let launch_options = crate::core::launch::LaunchOptions::from_args(std::env::args().skip(1));
+
+    // Just after a picture of the level? No need for a window then.
+    if launch_options.export_svg.is_some() {
+        std::process::exit(crate::core::level::export_svg_headless(&launch_options));
+    }
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -15,6 +22,7 @@ fn main() {
             }),
             ..Default::default()
         }))
+        .insert_resource(launch_options)
         .add_plugins(crate::core::CorePlugin)
         .run(); // Yay an app.
 }